@@ -0,0 +1,427 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    lib.rs
+@brief   Derive macro for IndentedDisplay
+ */
+
+//a Documentation
+/*!
+
+# Indent display derive
+
+This provides a derive macro for the `IndentedDisplay` trait of the
+`indent-display` crate; it is usually used through that crate with
+its `derive` feature enabled.
+
+A struct with named fields is displayed as its name followed by its
+fields, one per line, indented within braces:
+
+```text
+Server {
+  name: main,
+  port: 80,
+}
+```
+
+A tuple struct is displayed similarly within parentheses, with one
+field per line, and a unit struct is displayed as just its name.
+
+The type may be generic, with lifetimes and where clauses; the
+implementation is generic over the indenter lifetime `'indent` and
+(unless a specific options type is given) the options type
+`IndentOpt`. By default it requires every field type to implement
+`IndentedDisplay<'indent, IndentOpt>`.
+
+## Type attributes
+
+* `#[indent(options = "MyOptions")]` implements the trait only for
+  the given options type, rather than for all options types; this
+  permits field conditions to use methods of that type
+
+* `#[indent(bound = "T: IndentedDisplay<'indent, IndentOpt>")]`
+  replaces the inferred field type bounds with the given where
+  clause predicates (which may be empty), for cases where the
+  inferred bounds are wrong
+
+* `#[indent(layout = "inline")]` displays the value on a single line
+  without a trailing newline, such as `Point { x: 1, y: 2 }` or
+  `Point(1, 2)`; the default is `layout = "block"`. Each field is
+  displayed by its one-line summary (so a collection is shown by its
+  number of elements), and the summary of the value is its inline
+  display
+
+* `#[indent(indexed)]` labels the fields of a tuple struct with their
+  index, as `0: value`
+
+* `#[indent(doc_labels)]` labels each field with the first line of
+  its doc comment, if it has one, rather than its name
+
+## Field attributes
+
+* `#[indent(prefix = "├─ ")]` displays the field in a frame pushed
+  with the given indentation string, rather than the base
+  indentation, so that tree-style output can be derived; this may not
+  be used with the inline layout
+
+* `#[indent(if = "opts.show_internals()")]` displays the field only
+  if the expression is true; `opts` is the options of the indenter
+
+Fields whose values are empty (such as None or an empty collection)
+are omitted if the options elide empty values.
+
+Each field is displayed with its name (or index) as a label in the
+scope path of the indenter, so that the redaction hook of the options
+can identify it.
+
+In the block layout fields are displayed with `Indenter::value`, so
+they are summarized if they are too deep or too long for the options;
+the summary of the struct itself is its name, with `{…}` or `(…)` for
+its fields.
+
+!*/
+
+//a Imports
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Token};
+
+//a Type attributes
+//tp TypeAttrs
+/// The attributes that may be applied to the type with `#[indent(...)]`
+#[derive(Default)]
+struct TypeAttrs {
+    /// The specific options type to implement for, if any
+    options: Option<syn::Type>,
+    /// Explicit where-clause predicates to use instead of the
+    /// inferred field type bounds, if any
+    bound: Option<Vec<syn::WherePredicate>>,
+    /// Set if the type is to be displayed inline, rather than as a
+    /// block of lines
+    inline: bool,
+    /// Set if the fields of a tuple struct are to be labelled with
+    /// their index
+    indexed: bool,
+    /// Set if fields are to be labelled with the first line of their
+    /// doc comment, if they have one
+    doc_labels: bool,
+}
+
+//ip TypeAttrs
+impl TypeAttrs {
+    //fp parse
+    /// Parse the `indent` attributes of the type
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut type_attrs = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("indent")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("options") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    type_attrs.options = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("bound") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    let bound = s.parse_with(
+                        Punctuated::<syn::WherePredicate, Token![,]>::parse_terminated,
+                    )?;
+                    type_attrs.bound = Some(bound.into_iter().collect());
+                    Ok(())
+                } else if meta.path.is_ident("layout") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    match s.value().as_str() {
+                        "inline" => type_attrs.inline = true,
+                        "block" => type_attrs.inline = false,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                s,
+                                "layout must be 'inline' or 'block'",
+                            ));
+                        }
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("indexed") {
+                    type_attrs.indexed = true;
+                    Ok(())
+                } else if meta.path.is_ident("doc_labels") {
+                    type_attrs.doc_labels = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported indent attribute"))
+                }
+            })?;
+        }
+        Ok(type_attrs)
+    }
+}
+
+//a Field attributes
+//tp FieldAttrs
+/// The attributes that may be applied to a field with `#[indent(...)]`
+#[derive(Default)]
+struct FieldAttrs {
+    /// The indentation string to push for the field, if not the base
+    /// indentation
+    prefix: Option<LitStr>,
+    /// A condition, using `opts`, that must be true for the field to
+    /// be displayed
+    condition: Option<syn::Expr>,
+}
+
+//ip FieldAttrs
+impl FieldAttrs {
+    //fp parse
+    /// Parse the `indent` attributes of a field
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut field_attrs = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("indent")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("prefix") {
+                    field_attrs.prefix = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("if") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    field_attrs.condition = Some(s.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported indent attribute"))
+                }
+            })?;
+        }
+        Ok(field_attrs)
+    }
+}
+
+//a Derive
+//fi doc_first_line
+/// Get the first line of the doc comment from a set of attributes,
+/// if there is one and it is not blank
+fn doc_first_line(attrs: &[syn::Attribute]) -> Option<String> {
+    for attr in attrs.iter().filter(|a| a.path().is_ident("doc")) {
+        if let syn::Meta::NameValue(nv) = &attr.meta {
+            if let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(s),
+                ..
+            }) = &nv.value
+            {
+                let line = s.value().trim().to_string();
+                if !line.is_empty() {
+                    return Some(line);
+                }
+            }
+        }
+    }
+    None
+}
+
+//fi field_condition
+/// Wrap the display of a field in its condition, if it has one, and
+/// in the check for an empty value to be elided
+fn field_condition(
+    attrs: &FieldAttrs,
+    member: &TokenStream2,
+    display: TokenStream2,
+) -> TokenStream2 {
+    let display = quote! {
+        if !(::indent_display::IndentedOptions::elide_empty(f.options())
+            && ::indent_display::IndentedDisplay::is_empty_value(&self.#member))
+        #display
+    };
+    match &attrs.condition {
+        Some(condition) => quote! {
+            if { let opts = f.options(); #condition } { #display }
+        },
+        None => display,
+    }
+}
+
+//fi derive
+/// Generate the IndentedDisplay implementation for a type
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let type_attrs = TypeAttrs::parse(&input.attrs)?;
+    let opt_ty = match &type_attrs.options {
+        Some(ty) => quote! { #ty },
+        None => quote! { IndentOpt },
+    };
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let fields = match &input.data {
+        Data::Struct(s) => &s.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "IndentedDisplay can only be derived for structs",
+            ));
+        }
+    };
+    let named = matches!(fields, Fields::Named(_));
+
+    let mut field_types = Vec::new();
+    let mut field_displays = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        let path_label = match &field.ident {
+            Some(ident) => ident.to_string(),
+            None => i.to_string(),
+        };
+        let (member, label) = match &field.ident {
+            Some(ident) => (quote! { #ident }, Some(ident.to_string())),
+            None => {
+                let index = syn::Index::from(i);
+                let label = if type_attrs.indexed {
+                    Some(i.to_string())
+                } else {
+                    None
+                };
+                (quote! { #index }, label)
+            }
+        };
+        let label = match (type_attrs.doc_labels, doc_first_line(&field.attrs)) {
+            (true, Some(doc)) => Some(doc),
+            _ => label,
+        };
+        let label = label.map(|l| quote! { write!(sub, "{}: ", #l)?; });
+        field_types.push(&field.ty);
+        let display = if type_attrs.inline {
+            if let Some(prefix) = &attrs.prefix {
+                return Err(syn::Error::new_spanned(
+                    prefix,
+                    "prefix cannot be used with layout = \"inline\"",
+                ));
+            }
+            quote! {
+                {
+                    let sub = &mut *f;
+                    if !first {
+                        write!(sub, ", ")?;
+                    }
+                    first = false;
+                    #label
+                    sub.labelled(#path_label, |sub| {
+                        ::indent_display::IndentedDisplay::summarize(&self.#member, sub)
+                    })?;
+                }
+            }
+        } else {
+            let frame = match &attrs.prefix {
+                Some(prefix) => quote! { f.push(#prefix) },
+                None => quote! { f.sub() },
+            };
+            quote! {
+                {
+                    let mut sub = #frame;
+                    #label
+                    sub.labelled(#path_label, |sub| {
+                        sub.value(&self.#member)
+                    })?;
+                    writeln!(sub, ",")?;
+                }
+            }
+        };
+        field_displays.push(field_condition(&attrs, &member, display));
+    }
+
+    let body = match (fields.is_empty(), type_attrs.inline, named) {
+        (true, _, _) => quote! {
+            write!(f, "{}", #name_str)
+        },
+        (false, true, true) => quote! {
+            let mut first = true;
+            write!(f, "{} {{ ", #name_str)?;
+            #(#field_displays)*
+            let _ = first;
+            write!(f, " }}")
+        },
+        (false, true, false) => quote! {
+            let mut first = true;
+            write!(f, "{}(", #name_str)?;
+            #(#field_displays)*
+            let _ = first;
+            write!(f, ")")
+        },
+        (false, false, true) => quote! {
+            writeln!(f, "{} {{", #name_str)?;
+            #(#field_displays)*
+            writeln!(f, "}}")
+        },
+        (false, false, false) => quote! {
+            writeln!(f, "{}(", #name_str)?;
+            #(#field_displays)*
+            writeln!(f, ")")
+        },
+    };
+
+    let summary = match (fields.is_empty(), type_attrs.inline, named) {
+        (true, _, _) => quote! { write!(f, "{}", #name_str) },
+        (false, true, _) => body.clone(),
+        (false, false, true) => quote! { write!(f, "{} {{…}}", #name_str) },
+        (false, false, false) => quote! { write!(f, "{}(…)", #name_str) },
+    };
+
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, syn::parse_quote!('indent));
+    if type_attrs.options.is_none() {
+        generics.params.push(syn::parse_quote!(
+            IndentOpt: ::indent_display::IndentedOptions
+        ));
+    }
+    let where_clause = generics.make_where_clause();
+    match type_attrs.bound {
+        Some(bound) => {
+            where_clause.predicates.extend(bound);
+        }
+        None => {
+            for ty in field_types {
+                where_clause.predicates.push(
+                    syn::parse_quote!(#ty: ::indent_display::IndentedDisplay<'indent, #opt_ty>),
+                );
+            }
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::indent_display::IndentedDisplay<'indent, #opt_ty>
+            for #name #ty_generics #where_clause
+        {
+            fn indent(
+                &self,
+                f: &mut ::indent_display::Indenter<'indent, #opt_ty>,
+            ) -> ::std::fmt::Result {
+                use ::std::fmt::Write;
+                #body
+            }
+            fn summarize(
+                &self,
+                f: &mut ::indent_display::Indenter<'indent, #opt_ty>,
+            ) -> ::std::fmt::Result {
+                use ::std::fmt::Write;
+                #summary
+            }
+        }
+    })
+}
+
+//fp derive_indented_display
+/// Derive the `IndentedDisplay` trait for a struct
+#[proc_macro_derive(IndentedDisplay, attributes(indent))]
+pub fn derive_indented_display(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
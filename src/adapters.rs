@@ -0,0 +1,476 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    adapters.rs
+@brief   Adapter types that display other data through the indenter
+ */
+
+//a Imports
+use crate::defaults::indent_collection_elided;
+use crate::{BytesFormat, IndentedDisplay, IndentedOptions, Indenter, SizeEstimate};
+
+//a AsDisplay
+//tp AsDisplay
+/// A wrapper that displays any [std::fmt::Display] value through an
+/// [Indenter], for any options type
+///
+/// This permits values of types from other crates, which cannot be
+/// marked as [crate::DefaultIndentedDisplay], to be used where an
+/// [IndentedDisplay] is required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct AsDisplay<T: std::fmt::Display>(pub T);
+
+//ip IndentedDisplay for AsDisplay
+impl<'a, Opt: IndentedOptions, T: std::fmt::Display> IndentedDisplay<'a, Opt> for AsDisplay<T> {
+    //mp indent
+    /// Display the value using its [std::fmt::Display] implementation,
+    /// subject to the redaction hook of the options
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        let value = self.0.to_string();
+        let value = f.redacted(&value);
+        f.write_str(&value)
+    }
+}
+
+//a RenderedBlock
+//tp RenderedBlock
+/// The output of a value rendered with its own [Indenter], as lines
+/// with their depths, that may be sent between threads and later
+/// displayed through another [Indenter]
+///
+/// This permits the rendering of a large report to be split between
+/// worker threads, each producing a [RenderedBlock]; the blocks are
+/// then merged into the output in order, with [Indenter::merge].
+///
+/// When displayed, each line is output at its depth relative to the
+/// depth of the indenter it is displayed with, using the base
+/// indentation (explicit indentation strings used when rendering are
+/// not retained).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderedBlock {
+    /// The lines of the output and their depths
+    lines: Vec<(usize, String)>,
+}
+
+//ip RenderedBlock
+impl RenderedBlock {
+    //fp render
+    /// Render a value with the given options into a [RenderedBlock]
+    pub fn render<'a, Opt, T>(value: &T, options: &'a Opt) -> Self
+    where
+        Opt: IndentedOptions,
+        T: IndentedDisplay<'a, Opt> + ?Sized,
+    {
+        let lines = value.indented_structure(options);
+        Self { lines }
+    }
+
+    //ap lines
+    /// The lines of the rendered output, with their depths
+    pub fn lines(&self) -> &[(usize, String)] {
+        &self.lines
+    }
+}
+
+//ip IndentedDisplay for RenderedBlock
+impl<'a, Opt: IndentedOptions> IndentedDisplay<'a, Opt> for RenderedBlock {
+    //mp indent
+    /// Display the lines, each at its depth within the indenter
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        indent_lines(&mut self.lines.iter().peekable(), 0, f)
+    }
+}
+
+//fi indent_lines
+/// Display lines at a depth (and those deeper than it, in subframes)
+/// until a line shallower than it is reached
+fn indent_lines<'a, 'l, Opt, I>(
+    lines: &mut std::iter::Peekable<I>,
+    depth: usize,
+    f: &mut Indenter<'a, Opt>,
+) -> std::fmt::Result
+where
+    Opt: IndentedOptions,
+    I: Iterator<Item = &'l (usize, String)>,
+{
+    use std::fmt::Write;
+    while let Some((line_depth, text)) = lines.peek() {
+        if *line_depth < depth {
+            break;
+        } else if *line_depth > depth {
+            indent_lines(lines, depth + 1, &mut f.sub())?;
+        } else {
+            writeln!(f, "{}", text)?;
+            lines.next();
+        }
+    }
+    Ok(())
+}
+
+//a Elided
+//tp Elided
+/// An adapter that displays the elements of an iterator in the same
+/// manner as a slice, but showing at most `max` elements; any further
+/// elements are replaced by a single elision line
+///
+/// Only the elements that are displayed are generated, so this is
+/// safe to use with very large or unbounded iterators. The iterator
+/// is cloned for each display, so the adapter may be displayed many
+/// times.
+///
+/// If the iterator reports an exact size hint then the elision line
+/// reports how many elements were not shown ("… and N more");
+/// otherwise it is simply "… more". The text of these lines is taken
+/// from the options.
+#[derive(Debug, Clone)]
+pub struct Elided<I: Iterator + Clone> {
+    /// The iterator whose elements are to be displayed
+    iter: I,
+    /// The maximum number of elements to display
+    max: usize,
+}
+
+//ip Elided
+impl<I: Iterator + Clone> Elided<I> {
+    //fp new
+    /// Create a new [Elided] adapter from anything that can be
+    /// iterated over (with a cloneable iterator), showing at most
+    /// `max` elements
+    pub fn new<T: IntoIterator<IntoIter = I>>(iter: T, max: usize) -> Self {
+        let iter = iter.into_iter();
+        Self { iter, max }
+    }
+}
+
+//ip IndentedDisplay for Elided
+impl<'a, Opt, I> IndentedDisplay<'a, Opt> for Elided<I>
+where
+    Opt: IndentedOptions,
+    I: Iterator + Clone,
+    I::Item: IndentedDisplay<'a, Opt>,
+{
+    //mp indent
+    /// Display the first `max` elements, and an elision marker if
+    /// there are any more
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        let options = f.options();
+        let mut iter = self.iter.clone();
+        let elements: Vec<I::Item> = iter.by_ref().take(self.max).collect();
+        let elision = match iter.size_hint() {
+            (0, Some(0)) => None,
+            (n, Some(m)) if n == m => Some((options.elision_marker(), Some(n))),
+            _ => iter
+                .next()
+                .map(|_| (options.elision_marker_unknown(), None)),
+        };
+        let indices = options.slice_indices();
+        let estimate = |i: usize, x: &I::Item| {
+            let index_width = if indices { i.to_string().len() + 2 } else { 0 };
+            x.estimated_size(options).map(|e| SizeEstimate {
+                width: e.width + index_width,
+                ..e
+            })
+        };
+        let write = |f: &mut Indenter<'a, Opt>, i, x: &I::Item| {
+            if indices {
+                write!(f, "{}: ", i)?;
+            }
+            f.value(x)
+        };
+        let brackets = options.list_brackets();
+        indent_collection_elided(f, brackets, elements, elision, estimate, write)
+    }
+}
+
+//a Matrix
+//tp Matrix
+/// An adapter that displays a slice of rows (such as a `Vec<Vec<T>>`
+/// or `[[T; N]; M]`) as a matrix, with each row on its own line at the
+/// current indentation and the columns right-aligned to a common width
+///
+/// Rows need not be the same length; each column is as wide as the
+/// widest element in that column of any row.
+#[derive(Debug)]
+pub struct Matrix<'m, T, R: AsRef<[T]>> {
+    /// The rows of the matrix
+    rows: &'m [R],
+    /// Marker for the element type
+    phantom: std::marker::PhantomData<fn() -> T>,
+}
+
+//ip Matrix
+impl<'m, T, R: AsRef<[T]>> Matrix<'m, T, R> {
+    //fp new
+    /// Create a new [Matrix] adapter for a slice of rows
+    pub fn new(rows: &'m [R]) -> Self {
+        Self {
+            rows,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+//ip IndentedDisplay for Matrix
+impl<'a, 'm, Opt, T, R> IndentedDisplay<'a, Opt> for Matrix<'m, T, R>
+where
+    Opt: IndentedOptions,
+    T: std::fmt::Display,
+    R: AsRef<[T]>,
+{
+    //mp indent
+    /// Display the rows with right-aligned columns, with the elements
+    /// formatted using the numeric options
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let options = f.options();
+        let cells = self
+            .rows
+            .iter()
+            .map(|r| {
+                r.as_ref()
+                    .iter()
+                    .map(|x| format_number(x, options))
+                    .collect()
+            })
+            .collect();
+        indent_grid(f, cells)
+    }
+}
+
+//a Bytes
+//tp Bytes
+/// An adapter that displays byte data as hex, hex and ASCII, or
+/// base64
+///
+/// The format is taken from the options unless it is explicitly
+/// chosen with [Bytes::with_format]
+#[derive(Debug, Clone, Copy)]
+pub struct Bytes<'b> {
+    /// The data to display
+    data: &'b [u8],
+    /// The format to use, if not that of the options
+    format: Option<BytesFormat>,
+}
+
+//ip Bytes
+impl<'b> Bytes<'b> {
+    //fp new
+    /// Create a new [Bytes] adapter using the format from the options
+    pub fn new<T: AsRef<[u8]> + ?Sized>(data: &'b T) -> Self {
+        let data = data.as_ref();
+        Self { data, format: None }
+    }
+
+    //cp with_format
+    /// Use a specific format rather than that from the options
+    pub fn with_format(mut self, format: BytesFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+}
+
+//ip IndentedDisplay for Bytes
+impl<'a, 'b, Opt: IndentedOptions> IndentedDisplay<'a, Opt> for Bytes<'b> {
+    //mp indent
+    /// Display the bytes in the required format, as complete lines
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        let format = self.format.unwrap_or_else(|| f.options().bytes_format());
+        match format {
+            BytesFormat::Hex => {
+                for chunk in self.data.chunks(16) {
+                    for (i, b) in chunk.iter().enumerate() {
+                        let sep = if i == 0 { "" } else { " " };
+                        write!(f, "{}{:02x}", sep, b)?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            BytesFormat::HexAscii => {
+                for (n, chunk) in self.data.chunks(16).enumerate() {
+                    write!(f, "{:08x} ", n * 16)?;
+                    for i in 0..16 {
+                        if i == 8 {
+                            write!(f, " ")?;
+                        }
+                        match chunk.get(i) {
+                            Some(b) => write!(f, " {:02x}", b)?,
+                            None => write!(f, "   ")?,
+                        }
+                    }
+                    write!(f, "  |")?;
+                    for b in chunk {
+                        let c = if b.is_ascii_graphic() || *b == b' ' {
+                            *b as char
+                        } else {
+                            '.'
+                        };
+                        f.write_char(c)?;
+                    }
+                    writeln!(f, "|")?;
+                }
+            }
+            BytesFormat::Base64 => {
+                let encoded = base64_encode(self.data);
+                for line in encoded.as_bytes().chunks(76) {
+                    // base64 output is ASCII, so this cannot fail
+                    writeln!(f, "{}", std::str::from_utf8(line).unwrap())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//a Functions
+//fi base64_encode
+/// Encode data as standard base64 with padding
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let v = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | (b[2] as u32);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(ALPHABET[((v >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+//fi indent_grid
+/// Output a grid of cells as rows on consecutive lines, with columns
+/// right-aligned to a common width
+///
+/// Rows need not be the same length
+pub(crate) fn indent_grid<'a, Opt: IndentedOptions>(
+    f: &mut Indenter<'a, Opt>,
+    cells: Vec<Vec<String>>,
+) -> std::fmt::Result {
+    use std::fmt::Write;
+    let mut widths = Vec::new();
+    for row in cells.iter() {
+        for (i, c) in row.iter().enumerate() {
+            let w = crate::text::width(c);
+            if i >= widths.len() {
+                widths.push(w);
+            } else if w > widths[i] {
+                widths[i] = w;
+            }
+        }
+    }
+    for row in cells.iter() {
+        for (i, c) in row.iter().enumerate() {
+            let sep = if i == 0 { "" } else { " " };
+            write!(f, "{}{:>w$}", sep, c, w = widths[i])?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+//fi format_number
+/// Format a number (or any [std::fmt::Display] value) using the
+/// numeric options - the float precision, scientific thresholds and
+/// thousands separator (or the decimal formatter for a locale)
+///
+/// The value is formatted as text, which is then interpreted: a
+/// floating point value (or text with a fractional part or exponent)
+/// that parses as a finite number has the precision and scientific
+/// thresholds applied, text that parses as an integer is used as it
+/// is, and any other text is returned unchanged; the separator (or
+/// formatter) is applied only to numbers
+pub(crate) fn format_number<O, T>(x: &T, options: &O) -> String
+where
+    O: IndentedOptions,
+    T: std::fmt::Display + ?Sized,
+{
+    let text = x.to_string();
+    // A float always has a fractional part when formatted with a
+    // precision (even if its text has none, such as "0" for 0.0); an
+    // integer ignores the precision, and text is truncated by it
+    let is_float = text.contains(['.', 'e', 'E']) || format!("{:.1}", x).contains('.');
+    let text = if is_float {
+        match text.parse::<f64>() {
+            Ok(v) if v.is_finite() => format_float(text, v, options),
+            _ => return text,
+        }
+    } else if text.parse::<i128>().is_ok() || text.parse::<u128>().is_ok() {
+        text
+    } else {
+        return text;
+    };
+    #[cfg(feature = "icu")]
+    if let Some(formatter) = options.decimal_formatter() {
+        use crate::icu::decimal::input::Decimal;
+        if let Ok(decimal) = Decimal::try_from_str(&text) {
+            return formatter.format_to_string(&decimal);
+        }
+    }
+    match options.thousands_separator() {
+        Some(separator) => group_thousands(&text, separator),
+        None => text,
+    }
+}
+
+//fi format_float
+/// Format a floating point value with the precision and scientific
+/// thresholds of the options, returning its text unchanged if neither
+/// applies
+fn format_float<O: IndentedOptions>(text: String, v: f64, options: &O) -> String {
+    let precision = options.float_precision();
+    let scientific = options
+        .scientific_thresholds()
+        .is_some_and(|(small, large)| v != 0.0 && (v.abs() < small || v.abs() >= large));
+    match (scientific, precision) {
+        (true, Some(p)) => format!("{:.*e}", p, v),
+        (true, None) => format!("{:e}", v),
+        (false, Some(p)) => format!("{:.*}", p, v),
+        (false, None) => text,
+    }
+}
+
+//fi group_thousands
+/// Insert a separator between the groups of three digits of the
+/// integer part of numeric text; text that does not start with
+/// digits (after an optional sign) is returned unchanged
+fn group_thousands(text: &str, separator: &str) -> String {
+    let sign_len = if text.starts_with(['-', '+']) { 1 } else { 0 };
+    let (sign, rest) = text.split_at(sign_len);
+    let digits_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (digits, tail) = rest.split_at(digits_len);
+    if digits.is_empty() || text.parse::<f64>().is_err() {
+        return text.to_string();
+    }
+    let mut result = sign.to_string();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push_str(separator);
+        }
+        result.push(c);
+    }
+    result.push_str(tail);
+    result
+}
@@ -17,49 +17,636 @@ limitations under the License.
  */
 
 //a Imports
-use crate::{DefaultIndentedDisplay, IndentedDisplay, IndentedOptions, Indenter, NullOptions};
+use crate::adapters::format_number;
+use crate::sinks::{IoSink, Recorder};
+use crate::text;
+use crate::{
+    BasicOptions, DefaultIndentedDisplay, IndentStyle, IndentedDisplay, IndentedOptions, Indenter,
+    NullOptions, SizeEstimate,
+};
 
 //a DefaultIndentedDisplay implementation
 //ti IndentedDisplay for DefaultIndentedDisplay
-impl<'a, O: IndentedOptions<'a>, T: DefaultIndentedDisplay> IndentedDisplay<'a, O> for T {
+/// The value is displayed using its Display implementation (or its
+/// indented text, for numbers), subject to the redaction hook of the
+/// options
+impl<'a, O: IndentedOptions, T: DefaultIndentedDisplay> IndentedDisplay<'a, O> for T {
     fn indent(&self, ind: &mut Indenter<'a, O>) -> std::fmt::Result {
         use std::fmt::Write;
-        write!(ind, "{}", self)
+        let value = self.indented_text(ind.options());
+        let value = ind.redacted(&value);
+        ind.write_str(&value)
+    }
+    fn is_empty_value(&self) -> bool {
+        self.to_string().is_empty()
+    }
+    fn summarize(&self, ind: &mut Indenter<'a, O>) -> std::fmt::Result {
+        self.indent(ind)
+    }
+    fn estimated_size(&self, options: &'a O) -> Option<SizeEstimate> {
+        let text = self.indented_text(options);
+        let lines = text.lines().count().max(1);
+        let width = text.lines().map(text::width).max().unwrap_or(0);
+        Some(SizeEstimate { lines, width })
     }
 }
 
 //ti DefaultIndentedDisplay for base types
-impl DefaultIndentedDisplay for u8 {}
-impl DefaultIndentedDisplay for u16 {}
-impl DefaultIndentedDisplay for u32 {}
-impl DefaultIndentedDisplay for u64 {}
-impl DefaultIndentedDisplay for u128 {}
-impl DefaultIndentedDisplay for usize {}
-impl DefaultIndentedDisplay for i8 {}
-impl DefaultIndentedDisplay for i16 {}
-impl DefaultIndentedDisplay for i32 {}
-impl DefaultIndentedDisplay for i64 {}
-impl DefaultIndentedDisplay for i128 {}
-impl DefaultIndentedDisplay for isize {}
-impl DefaultIndentedDisplay for &str {}
-impl DefaultIndentedDisplay for String {}
-impl<'a, Opt: IndentedOptions<'a>, T: IndentedDisplay<'a, Opt>> IndentedDisplay<'a, Opt> for [T] {
+/// Numbers are formatted using the numeric options
+macro_rules! impl_numeric_indented {
+    ($($t:ty),*) => {
+        $(impl DefaultIndentedDisplay for $t {
+            fn indented_text<O: IndentedOptions>(&self, options: &O) -> String {
+                format_number(self, options)
+            }
+        })*
+    };
+}
+impl_numeric_indented!(u8, u16, u32, u64, u128, usize);
+impl_numeric_indented!(i8, i16, i32, i64, i128, isize);
+impl_numeric_indented!(f32, f64);
+crate::impl_default_indented!(&str, String);
+impl<'a, Opt: IndentedOptions, T: IndentedDisplay<'a, Opt>> IndentedDisplay<'a, Opt> for [T] {
     //mp fmt
     /// Display for humans with indent
     fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
         use std::fmt::Write;
-        write!(f, "[\n")?;
+        let options = f.options();
+        let indices = options.slice_indices();
+        let elements = self.iter().collect();
+        let estimate = |i: usize, x: &&T| {
+            let index_width = if indices { i.to_string().len() + 2 } else { 0 };
+            x.estimated_size(options).map(|e| SizeEstimate {
+                width: e.width + index_width,
+                ..e
+            })
+        };
+        let write = |f: &mut Indenter<'a, Opt>, i, x: &&T| {
+            if indices {
+                write!(f, "{}: ", i)?;
+            }
+            f.value(*x)
+        };
+        indent_collection(f, options.list_brackets(), elements, estimate, write)
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let estimates = self.iter().map(|x| x.estimated_size(options));
+        estimate_collection(options.list_brackets(), estimates)
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let brackets = f.options().list_brackets();
+        summarize_collection(f, brackets, self.len(), ("element", "elements"))
+    }
+}
+
+//ti IndentedDisplay for std maps and sets
+impl<'a, Opt, K, V, S> IndentedDisplay<'a, Opt> for std::collections::HashMap<K, V, S>
+where
+    Opt: IndentedOptions,
+    K: IndentedDisplay<'a, Opt>,
+    V: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let options = f.options();
+        if options.sort_unordered() {
+            let mut entries: Vec<_> = self
+                .iter()
+                .map(|(k, v)| (sort_key(k, options), (k, v)))
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            indent_map(f, entries.into_iter().map(|(_, e)| e))
+        } else {
+            indent_map(f, self.iter())
+        }
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let brackets = f.options().map_brackets();
+        summarize_collection(f, brackets, self.len(), ("entry", "entries"))
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let estimates = self.iter().map(|(k, v)| estimate_entry(k, v, options));
+        estimate_collection(options.map_brackets(), estimates)
+    }
+}
+impl<'a, Opt, K, V> IndentedDisplay<'a, Opt> for std::collections::BTreeMap<K, V>
+where
+    Opt: IndentedOptions,
+    K: IndentedDisplay<'a, Opt>,
+    V: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        indent_map(f, self.iter())
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let brackets = f.options().map_brackets();
+        summarize_collection(f, brackets, self.len(), ("entry", "entries"))
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let estimates = self.iter().map(|(k, v)| estimate_entry(k, v, options));
+        estimate_collection(options.map_brackets(), estimates)
+    }
+}
+impl<'a, Opt, T, S> IndentedDisplay<'a, Opt> for std::collections::HashSet<T, S>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let options = f.options();
+        if options.sort_unordered() {
+            let mut elements: Vec<_> = self.iter().map(|x| (sort_key(x, options), x)).collect();
+            elements.sort_by(|a, b| a.0.cmp(&b.0));
+            indent_set(f, elements.into_iter().map(|(_, x)| x))
+        } else {
+            indent_set(f, self.iter())
+        }
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let brackets = f.options().set_brackets();
+        summarize_collection(f, brackets, self.len(), ("element", "elements"))
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let estimates = self.iter().map(|x| x.estimated_size(options));
+        estimate_collection(options.set_brackets(), estimates)
+    }
+}
+impl<'a, Opt, T> IndentedDisplay<'a, Opt> for std::collections::BTreeSet<T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        indent_set(f, self.iter())
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let brackets = f.options().set_brackets();
+        summarize_collection(f, brackets, self.len(), ("element", "elements"))
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let estimates = self.iter().map(|x| x.estimated_size(options));
+        estimate_collection(options.set_brackets(), estimates)
+    }
+}
+
+//ti IndentedDisplay for Option and Result
+impl<'a, Opt, T> IndentedDisplay<'a, Opt> for Option<T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        match self {
+            Some(x) => indent_variant(f, "Some", x),
+            None => write!(f, "None"),
+        }
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_none()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        match self {
+            Some(x) => {
+                write!(f, "Some: ")?;
+                x.summarize(f)
+            }
+            None => write!(f, "None"),
+        }
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        match self {
+            Some(x) => x.estimated_size(options).map(|e| SizeEstimate {
+                lines: e.lines + 1,
+                width: e.width.max(5),
+            }),
+            None => Some(SizeEstimate { lines: 1, width: 4 }),
+        }
+    }
+}
+impl<'a, Opt, T, E> IndentedDisplay<'a, Opt> for Result<T, E>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+    E: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        match self {
+            Ok(x) => indent_variant(f, "Ok", x),
+            Err(e) => indent_variant(f, "Err", e),
+        }
+    }
+    fn is_empty_value(&self) -> bool {
+        match self {
+            Ok(x) => x.is_empty_value(),
+            Err(_) => false,
+        }
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        match self {
+            Ok(x) => {
+                write!(f, "Ok: ")?;
+                x.summarize(f)
+            }
+            Err(e) => {
+                write!(f, "Err: ")?;
+                e.summarize(f)
+            }
+        }
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let (e, header) = match self {
+            Ok(x) => (x.estimated_size(options)?, 3),
+            Err(e) => (e.estimated_size(options)?, 4),
+        };
+        Some(SizeEstimate {
+            lines: e.lines + 1,
+            width: e.width.max(header),
+        })
+    }
+}
+
+//ti IndentedDisplay for Mutex and RwLock
+/// The contents are displayed if the lock can be taken immediately;
+/// otherwise `<locked>` or `<poisoned>` is displayed, so displaying
+/// shared state never blocks
+impl<'a, Opt, T> IndentedDisplay<'a, Opt> for std::sync::Mutex<T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt> + ?Sized,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        use std::sync::TryLockError;
+        match self.try_lock() {
+            Ok(guard) => guard.indent(f),
+            Err(TryLockError::WouldBlock) => write!(f, "<locked>"),
+            Err(TryLockError::Poisoned(_)) => write!(f, "<poisoned>"),
+        }
+    }
+    fn is_empty_value(&self) -> bool {
+        self.try_lock().is_ok_and(|guard| guard.is_empty_value())
+    }
+}
+impl<'a, Opt, T> IndentedDisplay<'a, Opt> for std::sync::RwLock<T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt> + ?Sized,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        use std::sync::TryLockError;
+        match self.try_read() {
+            Ok(guard) => guard.indent(f),
+            Err(TryLockError::WouldBlock) => write!(f, "<locked>"),
+            Err(TryLockError::Poisoned(_)) => write!(f, "<poisoned>"),
+        }
+    }
+    fn is_empty_value(&self) -> bool {
+        self.try_read().is_ok_and(|guard| guard.is_empty_value())
+    }
+}
+
+//ti IndentedDisplay for Cell and RefCell
+/// The value of a Cell is copied out and displayed
+impl<'a, Opt, T> IndentedDisplay<'a, Opt> for std::cell::Cell<T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt> + Copy,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        self.get().indent(f)
+    }
+    fn is_empty_value(&self) -> bool {
+        self.get().is_empty_value()
+    }
+}
+/// The contents of a RefCell are displayed if they can be borrowed;
+/// otherwise (if it is mutably borrowed) `<borrowed>` is displayed
+impl<'a, Opt, T> IndentedDisplay<'a, Opt> for std::cell::RefCell<T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt> + ?Sized,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        match self.try_borrow() {
+            Ok(x) => x.indent(f),
+            Err(_) => write!(f, "<borrowed>"),
+        }
+    }
+    fn is_empty_value(&self) -> bool {
+        self.try_borrow().is_ok_and(|x| x.is_empty_value())
+    }
+}
+
+//a Variant functions
+//fi indent_variant
+/// Display a variant of an enum (such as Some, Ok or Err) as a
+/// header line 'Name:' with the payload indented beneath
+pub(crate) fn indent_variant<'a, Opt, T>(
+    f: &mut Indenter<'a, Opt>,
+    name: &str,
+    x: &T,
+) -> std::fmt::Result
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt> + ?Sized,
+{
+    use std::fmt::Write;
+    writeln!(f, "{}:", name)?;
+    let mut sub = f.sub();
+    sub.value(x)
+}
+
+//a Collection functions
+//fi indent_collection
+/// Display the elements of a collection within brackets, using the
+/// collection style of the options, with each element displayed by
+/// a function given its index
+///
+/// The elements are displayed on a single line if there are fewer
+/// than the inline threshold and each is short (displayed as a single
+/// line within the inline element width); otherwise they are
+/// displayed one per line, indented, with commas as required
+pub(crate) fn indent_collection<'a, Opt, E, S, W>(
+    f: &mut Indenter<'a, Opt>,
+    brackets: (&str, &str),
+    elements: Vec<E>,
+    estimate: S,
+    write: W,
+) -> std::fmt::Result
+where
+    Opt: IndentedOptions,
+    S: Fn(usize, &E) -> Option<SizeEstimate>,
+    W: Fn(&mut Indenter<'a, Opt>, usize, &E) -> std::fmt::Result,
+{
+    indent_collection_elided(f, brackets, elements, None, estimate, write)
+}
+
+//fi indent_collection_elided
+/// Display the elements of a collection as for [indent_collection],
+/// followed by an elision marker (a template and the count of
+/// elements not shown, as for [Indenter::marker]) if there is one
+///
+/// The marker is the last entry of the collection, and is never
+/// followed by a comma
+pub(crate) fn indent_collection_elided<'a, Opt, E, S, W>(
+    f: &mut Indenter<'a, Opt>,
+    brackets: (&str, &str),
+    elements: Vec<E>,
+    elision: Option<(&str, Option<usize>)>,
+    estimate: S,
+    write: W,
+) -> std::fmt::Result
+where
+    Opt: IndentedOptions,
+    S: Fn(usize, &E) -> Option<SizeEstimate>,
+    W: Fn(&mut Indenter<'a, Opt>, usize, &E) -> std::fmt::Result,
+{
+    use std::fmt::Write;
+    let (open, close) = brackets;
+    let options = f.options();
+    if is_inline(options, &elements, elision, &estimate, &write) {
+        write!(f, "{}", open)?;
+        for (i, e) in elements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write(f, i, e)?;
+        }
+        if let Some((template, n)) = elision {
+            if !elements.is_empty() {
+                write!(f, ", ")?;
+            }
+            f.marker(template, n)?;
+        }
+        return writeln!(f, "{}", close);
+    }
+    writeln!(f, "{}", open)?;
+    {
+        let mut sub = f.sub();
+        let trailing_commas = options.trailing_commas() || elision.is_some();
+        let n = elements.len();
+        for (i, e) in elements.iter().enumerate() {
+            write(&mut sub, i, e)?;
+            if trailing_commas || i + 1 < n {
+                writeln!(sub, ",")?;
+            } else {
+                writeln!(sub)?;
+            }
+        }
+        if let Some((template, n)) = elision {
+            sub.marker(template, n)?;
+            writeln!(sub)?;
+        }
+    }
+    writeln!(f, "{}", close)
+}
+
+//fi is_inline
+/// Determine if the elements of a collection are to be displayed on
+/// a single line, using the estimated size of each element or (if it
+/// has none) by displaying it with the options and no indentation
+///
+/// An elision marker counts as an element
+fn is_inline<'a, Opt, E, S, W>(
+    options: &'a Opt,
+    elements: &[E],
+    elision: Option<(&str, Option<usize>)>,
+    estimate: &S,
+    write: &W,
+) -> bool
+where
+    Opt: IndentedOptions,
+    S: Fn(usize, &E) -> Option<SizeEstimate>,
+    W: Fn(&mut Indenter<'a, Opt>, usize, &E) -> std::fmt::Result,
+{
+    let count = elements.len() + usize::from(elision.is_some());
+    if options.inline_threshold().is_none_or(|n| count >= n) {
+        return false;
+    }
+    let max_width = options.inline_element_width();
+    if let Some((template, n)) = elision {
+        let n = n.map(|n| n.to_string()).unwrap_or_default();
+        if text::width(&template.replace("{n}", &n)) > max_width {
+            return false;
+        }
+    }
+    elements.iter().enumerate().all(|(i, e)| {
+        if let Some(estimate) = estimate(i, e) {
+            return estimate.lines <= 1 && estimate.width <= max_width;
+        }
+        let recorder = Recorder::default();
         {
-            let mut sub = f.sub();
-            for x in self.iter() {
-                x.indent(&mut sub)?;
-                write!(sub, ",\n")?;
+            let sink = Box::new(IoSink::new(recorder.clone()));
+            let mut ind = Indenter::with_sink(sink, "", options);
+            if write(&mut ind, i, e).is_err() {
+                return false;
             }
         }
-        write!(f, "]\n")
+        let text = recorder.take();
+        !text.contains('\n') && text::width(&text) <= max_width
+    })
+}
+
+//fi indent_map
+/// Display the entries of a map, in iteration order, as 'key: value'
+/// within the map brackets
+///
+/// Entries with empty values are omitted if the options elide empty
+/// values
+pub(crate) fn indent_map<'a, 'x, Opt, K, V, I>(
+    f: &mut Indenter<'a, Opt>,
+    iter: I,
+) -> std::fmt::Result
+where
+    Opt: IndentedOptions,
+    K: IndentedDisplay<'a, Opt> + 'x,
+    V: IndentedDisplay<'a, Opt> + 'x,
+    I: Iterator<Item = (&'x K, &'x V)>,
+{
+    use std::fmt::Write;
+    let options = f.options();
+    let elide_empty = options.elide_empty();
+    let entries = iter
+        .filter(|(_, v)| !(elide_empty && v.is_empty_value()))
+        .collect();
+    let estimate = |_, (k, v): &(&K, &V)| estimate_entry(*k, *v, options);
+    let write = |f: &mut Indenter<'a, Opt>, _, (k, v): &(&K, &V)| {
+        k.indent(f)?;
+        write!(f, ": ")?;
+        f.value(*v)
+    };
+    indent_collection(f, options.map_brackets(), entries, estimate, write)
+}
+
+//fi estimate_entry
+/// Estimate the size of an entry of a map, displayed as 'key: value'
+pub(crate) fn estimate_entry<'a, Opt, K, V>(k: &K, v: &V, options: &'a Opt) -> Option<SizeEstimate>
+where
+    Opt: IndentedOptions,
+    K: IndentedDisplay<'a, Opt> + ?Sized,
+    V: IndentedDisplay<'a, Opt> + ?Sized,
+{
+    let k = k.estimated_size(options)?;
+    let v = v.estimated_size(options)?;
+    Some(SizeEstimate {
+        lines: k.lines + v.lines - 1,
+        width: k.width + 2 + v.width,
+    })
+}
+
+//fi estimate_collection
+/// Estimate the size of a collection displayed one element per line,
+/// given the estimated sizes of its elements; None if any element
+/// has no estimate
+pub(crate) fn estimate_collection<I>(brackets: (&str, &str), estimates: I) -> Option<SizeEstimate>
+where
+    I: Iterator<Item = Option<SizeEstimate>>,
+{
+    let mut size = SizeEstimate {
+        lines: 2,
+        width: text::width(brackets.0).max(text::width(brackets.1)),
+    };
+    for estimate in estimates {
+        let estimate = estimate?;
+        size.lines += estimate.lines;
+        size.width = size.width.max(estimate.width + 1);
     }
+    Some(size)
+}
+
+//fi summarize_collection
+/// Display the summary of a collection - the number of elements
+/// within its brackets, such as `[… 3 elements]`
+pub(crate) fn summarize_collection<'a, Opt: IndentedOptions>(
+    f: &mut Indenter<'a, Opt>,
+    brackets: (&str, &str),
+    n: usize,
+    nouns: (&str, &str),
+) -> std::fmt::Result {
+    use std::fmt::Write;
+    let noun = if n == 1 { nouns.0 } else { nouns.1 };
+    write!(f, "{}… {} {}{}", brackets.0, n, noun, brackets.1)
+}
+
+//fi sort_key
+/// The key used to sort the elements of an unordered collection - the
+/// element rendered with the options and no indentation
+pub(crate) fn sort_key<'a, Opt, T>(x: &T, options: &'a Opt) -> String
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt> + ?Sized,
+{
+    x.rendered_lines("", options).collect::<Vec<_>>().join("\n")
+}
+
+//fi indent_set
+/// Display the elements of a set, in iteration order, within the set
+/// brackets
+pub(crate) fn indent_set<'a, 'x, Opt, T, I>(f: &mut Indenter<'a, Opt>, iter: I) -> std::fmt::Result
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt> + 'x,
+    I: Iterator<Item = &'x T>,
+{
+    let options = f.options();
+    let estimate = |_, x: &&T| x.estimated_size(options);
+    let write = |f: &mut Indenter<'a, Opt>, _, x: &&T| f.value(*x);
+    indent_collection(f, options.set_brackets(), iter.collect(), estimate, write)
 }
 
 //a NullOptions
 //ti IndentedOptions
-impl IndentedOptions<'_> for NullOptions {}
+impl IndentedOptions for NullOptions {}
+
+//ti IndentedOptions for ()
+/// The unit type may be used as the options, with the default
+/// options as for [NullOptions]
+impl IndentedOptions for () {}
+
+//a BasicOptions
+//ti IndentedOptions for BasicOptions
+impl IndentedOptions for BasicOptions {
+    fn indent_width(&self) -> Option<usize> {
+        self.indent_width
+    }
+    fn indent_style(&self) -> IndentStyle {
+        self.indent_style
+    }
+    fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+    fn max_width(&self) -> Option<usize> {
+        self.max_width
+    }
+    fn verbosity(&self) -> usize {
+        self.verbosity
+    }
+    fn color(&self) -> bool {
+        self.color
+    }
+    fn line_ending(&self) -> &str {
+        self.line_ending
+    }
+    fn max_blank_lines(&self) -> usize {
+        self.max_blank_lines
+    }
+}
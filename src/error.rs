@@ -0,0 +1,227 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    error.rs
+@brief   Error type for the indenter
+ */
+
+//a IndentError
+//tp IndentError
+/// The cause of an [Error] - a failure of the output, or a condition
+/// signalled by a policy of the options
+///
+/// Further conditions may be added, so matches on this must have a
+/// wildcard arm
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IndentError {
+    /// The underlying writer failed
+    Io(std::io::Error),
+    /// An implementation of [crate::IndentedDisplay] (or of
+    /// [std::fmt::Display]) returned an error
+    Fmt,
+    /// A value was deeper than the maximum depth of the options, so
+    /// it was summarized; or the output of a frame was, so it was
+    /// elided
+    DepthExceeded {
+        /// The depth of the value
+        depth: usize,
+        /// The maximum depth of the options
+        max: usize,
+    },
+    /// A line exceeded the maximum width of the options (with
+    /// [crate::Overflow::Error])
+    WidthExceeded {
+        /// The width of the line
+        width: usize,
+        /// The maximum width of the options
+        max: usize,
+    },
+    /// The output exceeded the maximum number of lines of the
+    /// options, so the rest of it was discarded
+    LinesExceeded {
+        /// The maximum number of lines of the options
+        max: usize,
+    },
+    /// A line was truncated at the maximum width of the options (with
+    /// [crate::Overflow::Truncate])
+    Truncated {
+        /// The width the line would have had
+        width: usize,
+        /// The maximum width of the options
+        max: usize,
+    },
+}
+
+//ip IndentError
+impl IndentError {
+    //mp is_failure
+    /// Return true if this is a failure to produce the output (rather
+    /// than a report that the output was abridged as the options
+    /// require)
+    ///
+    /// Failures are reported by [crate::Indenter::last_error]; other
+    /// conditions by [crate::Indenter::take_abridgements]
+    pub fn is_failure(&self) -> bool {
+        !matches!(
+            self,
+            IndentError::DepthExceeded { .. }
+                | IndentError::LinesExceeded { .. }
+                | IndentError::Truncated { .. }
+        )
+    }
+}
+
+//ip Display for IndentError
+impl std::fmt::Display for IndentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IndentError::Io(e) => write!(f, "{}", e),
+            IndentError::Fmt => write!(f, "formatting error"),
+            IndentError::DepthExceeded { depth, max } => {
+                write!(f, "depth {} exceeds maximum depth of {}", depth, max)
+            }
+            IndentError::WidthExceeded { width, max } => {
+                write!(f, "line width {} exceeds maximum width of {}", width, max)
+            }
+            IndentError::LinesExceeded { max } => {
+                write!(f, "output exceeds maximum of {} lines", max)
+            }
+            IndentError::Truncated { width, max } => {
+                write!(
+                    f,
+                    "line width {} truncated to maximum width of {}",
+                    width, max
+                )
+            }
+        }
+    }
+}
+
+//a Error
+//tp Result
+/// The result of producing output with an [crate::Indenter], such as
+/// from [crate::IndentedDisplay::try_indent]
+pub type Result<T> = std::result::Result<T, Error>;
+
+//tp Error
+/// An error that occurred while producing the output of an
+/// [crate::Indenter]
+///
+/// The [std::fmt::Write] trait can only report a bare
+/// [std::fmt::Error]; the indenter records the cause and the path of
+/// named scopes that were active when it occurred, so that the
+/// failure can be diagnosed after the render.
+#[derive(Debug)]
+pub struct Error {
+    /// The names of the scopes enclosing the failed write, outermost
+    /// first
+    path: Vec<String>,
+    /// The cause of the error
+    kind: IndentError,
+}
+
+//ip Error
+impl Error {
+    //fp new
+    /// Create a new [Error] for a scope path and cause
+    pub fn new<K: Into<IndentError>>(path: Vec<String>, kind: K) -> Self {
+        let kind = kind.into();
+        Self { path, kind }
+    }
+
+    //ap path
+    /// The names of the scopes enclosing the failed write, outermost
+    /// first
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    //mp scope_path
+    /// The scope path as a single string, such as `config > servers[3] > tls`
+    pub fn scope_path(&self) -> String {
+        self.path.join(" > ")
+    }
+
+    //ap kind
+    /// Borrow the cause of the error
+    pub fn kind(&self) -> &IndentError {
+        &self.kind
+    }
+
+    //ap io_error
+    /// Borrow the underlying IO error, if the writer failed
+    pub fn io_error(&self) -> Option<&std::io::Error> {
+        match &self.kind {
+            IndentError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    //cp into_io_error
+    /// Convert into an IO error - the underlying IO error if the
+    /// writer failed, otherwise an error (of kind InvalidData, or
+    /// Other for a formatting error) that describes this one
+    pub fn into_io_error(self) -> std::io::Error {
+        match self.kind {
+            IndentError::Io(e) => e,
+            _ => std::io::Error::new(self.io_kind(), self),
+        }
+    }
+
+    //ap io_kind
+    /// The kind of IO error that corresponds to the error
+    pub(crate) fn io_kind(&self) -> std::io::ErrorKind {
+        match &self.kind {
+            IndentError::Io(e) => e.kind(),
+            IndentError::Fmt => std::io::ErrorKind::Other,
+            _ => std::io::ErrorKind::InvalidData,
+        }
+    }
+}
+
+//ip From<std::io::Error> for IndentError
+impl From<std::io::Error> for IndentError {
+    fn from(e: std::io::Error) -> Self {
+        IndentError::Io(e)
+    }
+}
+
+//ip From<std::fmt::Error> for IndentError
+impl From<std::fmt::Error> for IndentError {
+    fn from(_: std::fmt::Error) -> Self {
+        IndentError::Fmt
+    }
+}
+
+//ip Display for Error
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{}: {}", self.scope_path(), self.kind)
+        }
+    }
+}
+
+//ip std::error::Error for Error
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            IndentError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
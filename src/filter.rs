@@ -0,0 +1,122 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    filter.rs
+@brief   Scope filters for the indenter
+ */
+
+//a ScopeFilterError
+//tp ScopeFilterError
+/// An error in the specification of a [ScopeFilter]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeFilterError {
+    /// The directive that could not be parsed
+    directive: String,
+}
+
+//ip Display for ScopeFilterError
+impl std::fmt::Display for ScopeFilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "bad scope filter directive '{}'", self.directive)
+    }
+}
+
+//ip std::error::Error for ScopeFilterError
+impl std::error::Error for ScopeFilterError {}
+
+//a ScopeFilter
+//tp ScopeFilter
+/// A filter that controls which named scopes of an
+/// [crate::Indenter] produce output, supplied by
+/// [crate::IndentedOptions::scope_filter]
+///
+/// The filter is parsed from a comma-separated list of directives in
+/// the style of `RUST_LOG`, such as `"servers.*.tls=on, metrics=off"`.
+/// Each directive is a pattern of scope names separated by `.`, where
+/// `*` matches any single name, and a setting of `on` or `off` (a
+/// pattern on its own is `on`).
+///
+/// A pattern applies to a scope if it matches the start of the scope
+/// path; the longest pattern that applies determines whether the
+/// scope (and the scopes within it, unless another pattern applies
+/// to them) produces output. Scopes to which no pattern applies
+/// inherit the setting of their enclosing scope.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeFilter {
+    /// The patterns, split into names, and their settings
+    directives: Vec<(Vec<String>, bool)>,
+}
+
+//ip ScopeFilter
+impl ScopeFilter {
+    //fp parse
+    /// Parse a filter from its specification
+    pub fn parse(spec: &str) -> Result<Self, ScopeFilterError> {
+        let mut directives = Vec::new();
+        for directive in spec.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            let error = || ScopeFilterError {
+                directive: directive.to_string(),
+            };
+            let (pattern, setting) = match directive.split_once('=') {
+                Some((pattern, setting)) => (pattern.trim(), setting.trim()),
+                None => (directive, "on"),
+            };
+            let setting = match setting {
+                "on" => true,
+                "off" => false,
+                _ => return Err(error()),
+            };
+            let pattern: Vec<String> = pattern.split('.').map(|s| s.trim().to_string()).collect();
+            if pattern.iter().any(|s| s.is_empty()) {
+                return Err(error());
+            }
+            directives.push((pattern, setting));
+        }
+        Ok(Self { directives })
+    }
+
+    //mp enabled
+    /// Determine whether a scope path is enabled by the filter, if
+    /// any of its patterns applies to it
+    ///
+    /// If several patterns of the same length apply, the last one
+    /// given wins
+    pub fn enabled<S: AsRef<str>>(&self, path: &[S]) -> Option<bool> {
+        let mut best: Option<(usize, bool)> = None;
+        for (pattern, setting) in self.directives.iter() {
+            if pattern.len() > path.len() {
+                continue;
+            }
+            let matches = pattern
+                .iter()
+                .zip(path.iter())
+                .all(|(p, s)| p == "*" || p == s.as_ref());
+            if matches && best.is_none_or(|(n, _)| pattern.len() >= n) {
+                best = Some((pattern.len(), *setting));
+            }
+        }
+        best.map(|(_, setting)| setting)
+    }
+}
+
+//ip FromStr for ScopeFilter
+impl std::str::FromStr for ScopeFilter {
+    type Err = ScopeFilterError;
+    fn from_str(s: &str) -> Result<Self, ScopeFilterError> {
+        Self::parse(s)
+    }
+}
@@ -0,0 +1,140 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_android.rs
+@brief   Android logcat output
+ */
+
+//a Imports
+use std::ffi::CString;
+use std::os::raw::c_int;
+
+//a LogcatPriority
+//tp LogcatPriority
+/// The priority of the lines written to the Android log by a
+/// [Logcat] writer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogcatPriority {
+    /// Verbose
+    Verbose,
+    /// Debug
+    Debug,
+    /// Info
+    Info,
+    /// Warning
+    Warn,
+    /// Error
+    Error,
+}
+
+//ip LogcatPriority
+impl LogcatPriority {
+    //mi as_prio
+    /// The Android log priority value
+    fn as_prio(self) -> c_int {
+        let prio = match self {
+            Self::Verbose => android_log_sys::LogPriority::VERBOSE,
+            Self::Debug => android_log_sys::LogPriority::DEBUG,
+            Self::Info => android_log_sys::LogPriority::INFO,
+            Self::Warn => android_log_sys::LogPriority::WARN,
+            Self::Error => android_log_sys::LogPriority::ERROR,
+        };
+        prio as c_int
+    }
+}
+
+//a Logcat
+//tp Logcat
+/// An [std::io::Write] that writes each completed line of its output
+/// to the Android log, with a tag and priority, so that the output of
+/// an [crate::Indenter] can be seen with logcat
+///
+/// Lines are buffered until they are complete; any incomplete final
+/// line is written when the writer is dropped.
+#[derive(Debug)]
+pub struct Logcat {
+    /// The tag for the lines
+    tag: CString,
+    /// The priority of the lines
+    priority: LogcatPriority,
+    /// The current incomplete line
+    line: Vec<u8>,
+}
+
+//ip Logcat
+impl Logcat {
+    //fp new
+    /// Create a new [Logcat] writer with a tag and priority
+    ///
+    /// Any NUL characters in the tag are removed
+    pub fn new(tag: &str, priority: LogcatPriority) -> Self {
+        let tag = c_string(tag.as_bytes());
+        Self {
+            tag,
+            priority,
+            line: Vec::new(),
+        }
+    }
+
+    //mi write_line
+    /// Write the current line to the log, and clear it
+    fn write_line(&mut self) {
+        let text = c_string(&self.line);
+        self.line.clear();
+        // Safety: the tag and text are valid NUL-terminated strings
+        // that outlive the call
+        unsafe {
+            android_log_sys::__android_log_write(
+                self.priority.as_prio(),
+                self.tag.as_ptr(),
+                text.as_ptr(),
+            );
+        }
+    }
+}
+
+//ip io::Write for Logcat
+impl std::io::Write for Logcat {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for b in buf {
+            if *b == b'\n' {
+                self.write_line();
+            } else {
+                self.line.push(*b);
+            }
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+//ip Drop for Logcat
+impl Drop for Logcat {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            self.write_line();
+        }
+    }
+}
+
+//a Functions
+//fi c_string
+/// Create a C string from bytes, removing any NUL characters
+fn c_string(bytes: &[u8]) -> CString {
+    let bytes: Vec<u8> = bytes.iter().copied().filter(|b| *b != 0).collect();
+    // All NUL characters have been removed, so this cannot fail
+    CString::new(bytes).unwrap()
+}
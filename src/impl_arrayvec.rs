@@ -0,0 +1,42 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_arrayvec.rs
+@brief   IndentedDisplay for arrayvec types (with the 'arrayvec' feature)
+ */
+
+//a Imports
+use crate::{IndentedDisplay, IndentedOptions, Indenter, SizeEstimate};
+
+//a IndentedDisplay implementations
+//ip IndentedDisplay for ArrayVec
+/// Array vectors are displayed as slices
+impl<'a, Opt, T, const CAP: usize> IndentedDisplay<'a, Opt> for arrayvec::ArrayVec<T, CAP>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        self.as_slice().indent(f)
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        self.as_slice().summarize(f)
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        self.as_slice().estimated_size(options)
+    }
+}
@@ -0,0 +1,93 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_bitflags.rs
+@brief   Adapter to display bitflags types (with the 'bitflags' feature)
+ */
+
+//a Imports
+use bitflags::parser::WriteHex;
+use bitflags::Flags;
+
+use crate::{IndentedDisplay, IndentedOptions, Indenter};
+
+//a FlagsDisplay
+//tp FlagsDisplay
+/// An adapter to display a value of a type generated by the bitflags
+/// crate through an [Indenter]
+///
+/// By default each set flag is displayed by name on its own line;
+/// in compact mode they are displayed on one line as 'A | B | C'. Any
+/// bits set that do not correspond to named flags are displayed in
+/// hex, and a value with no bits set is displayed as '(empty)'.
+#[derive(Debug)]
+pub struct FlagsDisplay<'f, F: Flags> {
+    /// The flags value to display
+    flags: &'f F,
+    /// Set if the flags should be displayed on a single line
+    compact: bool,
+}
+
+//ip FlagsDisplay
+impl<'f, F: Flags> FlagsDisplay<'f, F> {
+    //fp new
+    /// Create a new [FlagsDisplay] showing one flag per line
+    pub fn new(flags: &'f F) -> Self {
+        Self {
+            flags,
+            compact: false,
+        }
+    }
+
+    //cp compact
+    /// Display the flags on a single line, separated by ' | '
+    pub fn compact(mut self) -> Self {
+        self.compact = true;
+        self
+    }
+}
+
+//ip IndentedDisplay for FlagsDisplay
+impl<'a, 'f, Opt, F> IndentedDisplay<'a, Opt> for FlagsDisplay<'f, F>
+where
+    Opt: IndentedOptions,
+    F: Flags,
+    F::Bits: WriteHex,
+{
+    //mp indent
+    /// Display the names of the flags that are set, and any other bits
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        let mut names: Vec<String> = Vec::new();
+        let mut iter = self.flags.iter_names();
+        for (name, _) in iter.by_ref() {
+            names.push(name.to_string());
+        }
+        if !iter.remaining().is_empty() {
+            let mut hex = String::from("0x");
+            iter.remaining().bits().write_hex(&mut hex)?;
+            names.push(hex);
+        }
+        if names.is_empty() {
+            writeln!(f, "(empty)")
+        } else if self.compact {
+            writeln!(f, "{}", names.join(" | "))
+        } else {
+            for n in names {
+                writeln!(f, "{}", n)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,39 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_either.rs
+@brief   IndentedDisplay for either::Either (with the 'either' feature)
+ */
+
+//a Imports
+use crate::defaults::indent_variant;
+use crate::{IndentedDisplay, IndentedOptions, Indenter};
+
+//a IndentedDisplay implementations
+//ip IndentedDisplay for Either
+/// The active variant is displayed as a 'Left:' or 'Right:' header
+/// with the payload indented beneath, as for Result
+impl<'a, Opt, L, R> IndentedDisplay<'a, Opt> for either::Either<L, R>
+where
+    Opt: IndentedOptions,
+    L: IndentedDisplay<'a, Opt>,
+    R: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        match self {
+            either::Either::Left(l) => indent_variant(f, "Left", l),
+            either::Either::Right(r) => indent_variant(f, "Right", r),
+        }
+    }
+}
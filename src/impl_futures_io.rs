@@ -0,0 +1,290 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_futures_io.rs
+@brief   Asynchronous indented output to a futures-io AsyncWrite
+ */
+
+//a Imports
+use std::borrow::Cow;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+
+use futures_io::AsyncWrite;
+
+use crate::sinks::{IoSink, OwnedWriter};
+use crate::{Error, IndentedDisplay, IndentedOptions, Indenter};
+
+//a AsyncIndentedDisplay
+//tp AsyncIndentedDisplay
+/// A trait for values that display themselves through an
+/// [AsyncIndenter], so that their output may be streamed to the
+/// writer as it is produced rather than rendered in full first
+///
+/// An implementation writes through [AsyncIndenter::write_with] (or
+/// [AsyncIndenter::display] for parts of the value that implement
+/// [IndentedDisplay]), and awaits [AsyncIndenter::send] whenever the
+/// output so far should be written.
+pub trait AsyncIndentedDisplay<'a, Opt: IndentedOptions> {
+    //mp indent_async
+    /// Display the value through the [AsyncIndenter]
+    fn indent_async<W: AsyncWrite + Unpin>(
+        &self,
+        f: &mut AsyncIndenter<'a, W, Opt>,
+    ) -> impl Future<Output = std::io::Result<()>>;
+}
+
+//ip AsyncIndentedDisplay for [T]
+impl<'a, Opt, T> AsyncIndentedDisplay<'a, Opt> for [T]
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    //mp indent_async
+    /// Display the elements as for a slice, writing each element to
+    /// the writer once it is rendered
+    ///
+    /// If the options would display the slice on a single line (see
+    /// [IndentedOptions::inline_threshold]) then it is displayed as
+    /// for [AsyncIndenter::display], so the output is the same
+    fn indent_async<W: AsyncWrite + Unpin>(
+        &self,
+        f: &mut AsyncIndenter<'a, W, Opt>,
+    ) -> impl Future<Output = std::io::Result<()>> {
+        use std::fmt::Write;
+        async move {
+            let options = f.options();
+            let short = options.inline_threshold().is_some_and(|n| self.len() < n);
+            if short && Indenter::measure(self, "", options).lines <= 1 {
+                return f.display(self).await;
+            }
+            let (open, close) = options.list_brackets();
+            f.write_with(|f| writeln!(f, "{}", open))?;
+            f.sub();
+            let n = self.len();
+            for (i, x) in self.iter().enumerate() {
+                f.write_with(|f| {
+                    if options.slice_indices() {
+                        write!(f, "{}: ", i)?;
+                    }
+                    f.value(x)?;
+                    if options.trailing_commas() || i + 1 < n {
+                        writeln!(f, ",")
+                    } else {
+                        writeln!(f)
+                    }
+                })?;
+                f.send().await?;
+            }
+            f.pop();
+            f.write_with(|f| writeln!(f, "{}", close))?;
+            f.send().await
+        }
+    }
+}
+
+//ip AsyncIndentedDisplay for Vec<T>
+impl<'a, Opt, T> AsyncIndentedDisplay<'a, Opt> for Vec<T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    //mp indent_async
+    /// Display the elements as for a slice
+    fn indent_async<W: AsyncWrite + Unpin>(
+        &self,
+        f: &mut AsyncIndenter<'a, W, Opt>,
+    ) -> impl Future<Output = std::io::Result<()>> {
+        self.as_slice().indent_async(f)
+    }
+}
+
+//a FrameStack
+//ti FrameStack
+/// The frames of an [AsyncIndenter], the first being the base
+/// [Indenter]; they are dropped innermost first
+struct FrameStack<'a, Opt: IndentedOptions>(Vec<Indenter<'a, Opt>>);
+
+//ii Drop for FrameStack
+impl<'a, Opt: IndentedOptions> Drop for FrameStack<'a, Opt> {
+    fn drop(&mut self) {
+        while self.0.pop().is_some() {}
+    }
+}
+
+//a AsyncIndenter
+//tp AsyncIndenter
+/// An indenter that writes to a futures-io [AsyncWrite], for async
+/// services that stream indented output (such as over a socket)
+///
+/// The output is rendered by an [Indenter] into a buffer, which is
+/// written to the writer when [AsyncIndenter::send] (or
+/// [AsyncIndenter::display]) is awaited; so only the output since
+/// the last send is held in memory.
+///
+/// As the frames of the indenter are held across awaits they are
+/// pushed and popped explicitly, with [AsyncIndenter::sub],
+/// [AsyncIndenter::push] and [AsyncIndenter::pop], rather than
+/// being popped when dropped.
+pub struct AsyncIndenter<'a, W, Opt: IndentedOptions> {
+    /// The frames, innermost last
+    frames: FrameStack<'a, Opt>,
+    /// The buffer that the indenter writes to
+    buffer: OwnedWriter<Vec<u8>>,
+    /// The writer that the buffer is sent to
+    writer: W,
+}
+
+//ip AsyncIndenter
+impl<'a, W: AsyncWrite + Unpin, Opt: IndentedOptions> AsyncIndenter<'a, W, Opt> {
+    //fp new
+    /// Create a new [AsyncIndenter] for a writer, with the base
+    /// indentation string and options as for [Indenter::new]
+    pub fn new<S: Into<Cow<'a, str>>>(writer: W, s: S, options: &'a Opt) -> Self {
+        let buffer = OwnedWriter::new(Vec::new());
+        let indenter = Indenter::with_sink(Box::new(IoSink::new(buffer.clone())), s, options);
+        Self {
+            frames: FrameStack(vec![indenter]),
+            buffer,
+            writer,
+        }
+    }
+
+    //ap options
+    /// Get the options of the current frame
+    pub fn options(&self) -> &'a Opt {
+        self.indenter().options()
+    }
+
+    //ap indenter
+    /// Get the [Indenter] for the current frame
+    pub fn indenter(&self) -> &Indenter<'a, Opt> {
+        self.frames.0.last().unwrap()
+    }
+
+    //mp indenter_mut
+    /// Get the [Indenter] for the current frame mutably, to write to
+    /// it; the output is written to the writer when next sent
+    pub fn indenter_mut(&mut self) -> &mut Indenter<'a, Opt> {
+        self.frames.0.last_mut().unwrap()
+    }
+
+    //mp sub
+    /// Push a new frame using the base indentation, as for
+    /// [Indenter::sub]
+    pub fn sub(&mut self) {
+        let frame = self.indenter().detached(None);
+        self.frames.0.push(frame);
+    }
+
+    //mp push
+    /// Push a new frame using a specific indentation string, as for
+    /// [Indenter::push]
+    pub fn push<S: Into<Cow<'a, str>>>(&mut self, s: S) {
+        let frame = self.indenter().detached(Some(s.into()));
+        self.frames.0.push(frame);
+    }
+
+    //mp pop
+    /// Pop the current frame; popping the base frame has no effect
+    pub fn pop(&mut self) {
+        if self.frames.0.len() > 1 {
+            self.frames.0.pop();
+        }
+    }
+
+    //mp write_with
+    /// Invoke a function to write to the [Indenter] for the current
+    /// frame, returning any error as an IO error with the same kind
+    /// as the underlying error
+    pub fn write_with<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut Indenter<'a, Opt>) -> std::fmt::Result,
+    {
+        let indenter = self.indenter_mut();
+        let result = f(indenter);
+        let error = match (result, indenter.last_error()) {
+            (_, Some(e)) => e,
+            (Err(e), None) => Error::new(indenter.current_path(), e),
+            _ => return Ok(()),
+        };
+        Err(error.into_io_error())
+    }
+
+    //mp display
+    /// Display a value in the current frame and send the output
+    pub async fn display<T>(&mut self, x: &T) -> std::io::Result<()>
+    where
+        T: IndentedDisplay<'a, Opt> + ?Sized,
+    {
+        self.write_with(|f| x.indent(f))?;
+        self.send().await
+    }
+
+    //mp display_async
+    /// Display a value that implements [AsyncIndentedDisplay] in the
+    /// current frame, streaming its output
+    pub async fn display_async<T>(&mut self, x: &T) -> std::io::Result<()>
+    where
+        T: AsyncIndentedDisplay<'a, Opt> + ?Sized,
+    {
+        x.indent_async(self).await?;
+        self.send().await
+    }
+
+    //mp send
+    /// Write the output buffered so far to the writer
+    ///
+    /// The output of a line that is not yet complete may be held
+    /// back by the [Indenter] until it is
+    pub async fn send(&mut self) -> std::io::Result<()> {
+        let data = self.buffer.with(std::mem::take).unwrap_or_default();
+        write_all(&mut self.writer, &data).await
+    }
+
+    //mp flush
+    /// Write the output buffered so far to the writer, and flush it
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        self.send().await?;
+        poll_fn(|cx| Pin::new(&mut self.writer).poll_flush(cx)).await
+    }
+
+    //mp finish
+    /// Pop all of the frames, complete the output as for
+    /// [Indenter::finish], write it and flush the writer, and return
+    /// the writer
+    pub async fn finish(mut self) -> std::io::Result<W> {
+        let mut frames = std::mem::take(&mut self.frames.0);
+        while frames.len() > 1 {
+            frames.pop();
+        }
+        let result = frames.pop().unwrap().finish();
+        self.flush().await?;
+        result?;
+        Ok(self.writer)
+    }
+}
+
+//fi write_all
+/// Write all of a buffer to an [AsyncWrite]
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, mut buf: &[u8]) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        let n = poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, buf)).await?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        buf = &buf[n..];
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_indexmap.rs
+@brief   IndentedDisplay for indexmap types (with the 'indexmap' feature)
+ */
+
+//a Imports
+use crate::defaults::{
+    estimate_collection, estimate_entry, indent_map, indent_set, summarize_collection,
+};
+use crate::{IndentedDisplay, IndentedOptions, Indenter, SizeEstimate};
+
+//a IndentedDisplay implementations
+//ip IndentedDisplay for IndexMap
+/// Index maps are displayed as for the std maps, in insertion order
+impl<'a, Opt, K, V, S> IndentedDisplay<'a, Opt> for indexmap::IndexMap<K, V, S>
+where
+    Opt: IndentedOptions,
+    K: IndentedDisplay<'a, Opt>,
+    V: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        indent_map(f, self.iter())
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let brackets = f.options().map_brackets();
+        summarize_collection(f, brackets, self.len(), ("entry", "entries"))
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let estimates = self.iter().map(|(k, v)| estimate_entry(k, v, options));
+        estimate_collection(options.map_brackets(), estimates)
+    }
+}
+
+//ip IndentedDisplay for IndexSet
+/// Index sets are displayed as for the std sets, in insertion order
+impl<'a, Opt, T, S> IndentedDisplay<'a, Opt> for indexmap::IndexSet<T, S>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        indent_set(f, self.iter())
+    }
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let brackets = f.options().set_brackets();
+        summarize_collection(f, brackets, self.len(), ("element", "elements"))
+    }
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let estimates = self.iter().map(|x| x.estimated_size(options));
+        estimate_collection(options.set_brackets(), estimates)
+    }
+}
@@ -0,0 +1,44 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_nalgebra.rs
+@brief   IndentedDisplay for nalgebra types (with the 'nalgebra' feature)
+ */
+
+//a Imports
+use crate::adapters::{format_number, indent_grid};
+use crate::{IndentedDisplay, IndentedOptions, Indenter};
+
+//a IndentedDisplay implementations
+//ip IndentedDisplay for Matrix
+/// Matrices (including vectors) are displayed one row per line with
+/// the columns right-aligned; hence a column vector is displayed with
+/// one element per line
+impl<'a, Opt, T, R, C, S> IndentedDisplay<'a, Opt> for nalgebra::Matrix<T, R, C, S>
+where
+    Opt: IndentedOptions,
+    T: nalgebra::Scalar + std::fmt::Display,
+    R: nalgebra::Dim,
+    C: nalgebra::Dim,
+    S: nalgebra::RawStorage<T, R, C>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let options = f.options();
+        let cells = self
+            .row_iter()
+            .map(|r| r.iter().map(|x| format_number(x, options)).collect())
+            .collect();
+        indent_grid(f, cells)
+    }
+}
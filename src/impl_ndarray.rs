@@ -0,0 +1,57 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_ndarray.rs
+@brief   IndentedDisplay for ndarray types (with the 'ndarray' feature)
+ */
+
+//a Imports
+use crate::adapters::{format_number, indent_grid};
+use crate::{IndentedDisplay, IndentedOptions, Indenter};
+
+//a IndentedDisplay implementations
+//ip IndentedDisplay for ArrayBase (1D)
+/// One-dimensional arrays are displayed as a single row
+impl<'a, Opt, S, A> IndentedDisplay<'a, Opt> for ndarray::ArrayBase<S, ndarray::Ix1>
+where
+    Opt: IndentedOptions,
+    S: ndarray::Data<Elem = A>,
+    A: std::fmt::Display,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let options = f.options();
+        let row = self.iter().map(|x| format_number(x, options)).collect();
+        indent_grid(f, vec![row])
+    }
+}
+
+//ip IndentedDisplay for ArrayBase (2D)
+/// Two-dimensional arrays are displayed as a matrix, one row per line
+/// with the columns right-aligned
+impl<'a, Opt, S, A> IndentedDisplay<'a, Opt> for ndarray::ArrayBase<S, ndarray::Ix2>
+where
+    Opt: IndentedOptions,
+    S: ndarray::Data<Elem = A>,
+    A: std::fmt::Display,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        let options = f.options();
+        let cells = self
+            .rows()
+            .into_iter()
+            .map(|r| r.iter().map(|x| format_number(x, options)).collect())
+            .collect();
+        indent_grid(f, cells)
+    }
+}
@@ -0,0 +1,129 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_petgraph.rs
+@brief   Adapter to display petgraph graphs (with the 'petgraph' feature)
+ */
+
+//a Imports
+use petgraph::graph::{Graph, IndexType, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+
+use crate::{IndentedDisplay, IndentedOptions, Indenter};
+
+//a GraphDisplay
+//tp GraphDisplay
+/// An adapter to display a petgraph [Graph] through an [Indenter]
+///
+/// By default every node is displayed on its own line with its
+/// outgoing edges indented beneath it, as '-weight-> target'.
+///
+/// Alternatively the graph may be displayed as a spanning tree (or
+/// forest), where the target of each edge is displayed beneath the
+/// edge with its own edges indented further; an edge to a node that
+/// has already been displayed is shown with a back-edge marker '↩'
+/// and is not followed, so cycles are displayed safely.
+#[derive(Debug)]
+pub struct GraphDisplay<'g, N, E, Ty: EdgeType, Ix: IndexType> {
+    /// The graph to be displayed
+    graph: &'g Graph<N, E, Ty, Ix>,
+    /// If Some then display as a spanning tree from these roots,
+    /// followed by any nodes not reachable from them
+    roots: Option<Vec<NodeIndex<Ix>>>,
+}
+
+//ip GraphDisplay
+impl<'g, N, E, Ty: EdgeType, Ix: IndexType> GraphDisplay<'g, N, E, Ty, Ix> {
+    //fp new
+    /// Create a new [GraphDisplay] showing every node with its
+    /// outgoing edges
+    pub fn new(graph: &'g Graph<N, E, Ty, Ix>) -> Self {
+        Self { graph, roots: None }
+    }
+
+    //fp spanning_tree
+    /// Create a new [GraphDisplay] showing the graph as a spanning
+    /// tree from the given roots; any nodes not reachable from the
+    /// roots are then used as further roots in index order
+    pub fn spanning_tree(graph: &'g Graph<N, E, Ty, Ix>, roots: &[NodeIndex<Ix>]) -> Self {
+        let roots = Some(roots.to_vec());
+        Self { graph, roots }
+    }
+
+    //mi indent_tree
+    /// Display a node and, beneath it, the spanning tree of its
+    /// outgoing edges that lead to nodes not yet visited
+    fn indent_tree<'a, Opt: IndentedOptions>(
+        &self,
+        f: &mut Indenter<'a, Opt>,
+        node: NodeIndex<Ix>,
+        visited: &mut Vec<bool>,
+    ) -> std::fmt::Result
+    where
+        N: std::fmt::Display,
+        E: std::fmt::Display,
+    {
+        use std::fmt::Write;
+        visited[node.index()] = true;
+        writeln!(f, "{}", self.graph[node])?;
+        let mut sub = f.sub();
+        for edge in self.graph.edges(node) {
+            let target = edge.target();
+            if visited[target.index()] {
+                writeln!(sub, "-{}-> ↩ {}", edge.weight(), self.graph[target])?;
+            } else {
+                write!(sub, "-{}-> ", edge.weight())?;
+                self.indent_tree(&mut sub, target, visited)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//ip IndentedDisplay for GraphDisplay
+impl<'a, 'g, Opt, N, E, Ty, Ix> IndentedDisplay<'a, Opt> for GraphDisplay<'g, N, E, Ty, Ix>
+where
+    Opt: IndentedOptions,
+    N: std::fmt::Display,
+    E: std::fmt::Display,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    //mp indent
+    /// Display the graph, either flat or as a spanning tree
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        match &self.roots {
+            None => {
+                for node in self.graph.node_indices() {
+                    writeln!(f, "{}", self.graph[node])?;
+                    let mut sub = f.sub();
+                    for edge in self.graph.edges(node) {
+                        writeln!(sub, "-{}-> {}", edge.weight(), self.graph[edge.target()])?;
+                    }
+                }
+            }
+            Some(roots) => {
+                let mut visited = vec![false; self.graph.node_count()];
+                for node in roots.iter().copied().chain(self.graph.node_indices()) {
+                    if !visited[node.index()] {
+                        self.indent_tree(f, node, &mut visited)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
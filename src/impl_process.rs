@@ -0,0 +1,170 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_process.rs
+@brief   Indented output of child processes
+ */
+
+//a Imports
+use std::borrow::Cow;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+
+use crate::indenter::copy_line;
+use crate::{IndentedOptions, Indenter};
+
+//a ChildOutput
+//tp ChildOutput
+/// A helper that streams the stdout and stderr of a child process
+/// through an [Indenter], each line at the current indentation
+/// labelled with a prefix for its stream (as a build tool may do for
+/// the tools it runs)
+///
+/// The streams are read on separate threads, and their lines are
+/// written by the calling thread as they arrive; so the lines of the
+/// two streams are interleaved as the child produces them.
+#[derive(Debug, Clone)]
+pub struct ChildOutput<'p> {
+    /// The prefix of each line of stdout
+    stdout: Cow<'p, str>,
+    /// The prefix of each line of stderr
+    stderr: Cow<'p, str>,
+}
+
+//ip Default for ChildOutput
+impl Default for ChildOutput<'_> {
+    fn default() -> Self {
+        Self {
+            stdout: "stdout: ".into(),
+            stderr: "stderr: ".into(),
+        }
+    }
+}
+
+//ip ChildOutput
+impl<'p> ChildOutput<'p> {
+    //fp new
+    /// Create a new [ChildOutput], labelling the lines with "stdout: "
+    /// and "stderr: "
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //cp with_stdout_prefix
+    /// Set the prefix of each line of stdout
+    pub fn with_stdout_prefix<S: Into<Cow<'p, str>>>(mut self, prefix: S) -> Self {
+        self.stdout = prefix.into();
+        self
+    }
+
+    //cp with_stderr_prefix
+    /// Set the prefix of each line of stderr
+    pub fn with_stderr_prefix<S: Into<Cow<'p, str>>>(mut self, prefix: S) -> Self {
+        self.stderr = prefix.into();
+        self
+    }
+
+    //mp run
+    /// Spawn a command with its stdout and stderr piped, and stream
+    /// them through the [Indenter] until it exits, returning its exit
+    /// status
+    pub fn run<'a, Opt>(
+        &self,
+        command: &mut Command,
+        ind: &mut Indenter<'a, Opt>,
+    ) -> std::io::Result<ExitStatus>
+    where
+        'p: 'a,
+        Opt: IndentedOptions,
+    {
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        self.wait(&mut child, ind)
+    }
+
+    //mp wait
+    /// Stream the piped stdout and stderr of a child process through
+    /// the [Indenter] until they are closed, and wait for it to exit,
+    /// returning its exit status
+    ///
+    /// A stream that is not piped is not taken from the child, and
+    /// is not written.
+    pub fn wait<'a, Opt>(
+        &self,
+        child: &mut Child,
+        ind: &mut Indenter<'a, Opt>,
+    ) -> std::io::Result<ExitStatus>
+    where
+        'p: 'a,
+        Opt: IndentedOptions,
+    {
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let result = std::thread::scope(|s| {
+            let (tx, rx) = mpsc::channel();
+            if let Some(stdout) = stdout {
+                let tx = tx.clone();
+                s.spawn(move || read_lines(stdout, false, tx));
+            }
+            if let Some(stderr) = stderr {
+                let tx = tx.clone();
+                s.spawn(move || read_lines(stderr, true, tx));
+            }
+            drop(tx);
+            for (is_stderr, line) in rx {
+                let line = line?;
+                let prefix = if is_stderr {
+                    &self.stderr
+                } else {
+                    &self.stdout
+                };
+                copy_line(&line, &mut ind.push(prefix.clone()))?;
+            }
+            Ok(())
+        });
+        // The child is waited for even if the output failed, so that
+        // it is not left as a zombie
+        let status = child.wait();
+        result.and(status)
+    }
+}
+
+//fi read_lines
+/// Read the lines of a stream of a child, sending each (or an error)
+/// to the thread writing them, until it is closed
+fn read_lines<R: Read>(
+    stream: R,
+    is_stderr: bool,
+    tx: mpsc::Sender<(bool, std::io::Result<Vec<u8>>)>,
+) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = Vec::new();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return,
+            Ok(_) => {
+                if tx.send((is_stderr, Ok(line))).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                let _ = tx.send((is_stderr, Err(e)));
+                return;
+            }
+        }
+    }
+}
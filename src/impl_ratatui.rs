@@ -0,0 +1,106 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_ratatui.rs
+@brief   Rendering into ratatui text
+ */
+
+//a Imports
+use ratatui::style::Style;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::Paragraph;
+
+use crate::sinks::DepthRecorder;
+use crate::{IndentedDisplay, IndentedOptions, Indenter};
+
+//a RatatuiText
+//tp RatatuiText
+/// An adapter that renders a value through an [Indenter] into a
+/// ratatui [Text] (or [Paragraph]), so that the same
+/// [IndentedDisplay] implementations can be used for a terminal
+/// user interface
+///
+/// Each line of output becomes a [Line] with its indentation as one
+/// span and its text as another; the text may be styled according to
+/// its depth.
+#[derive(Debug)]
+pub struct RatatuiText<'v, T: ?Sized> {
+    /// The value to render
+    value: &'v T,
+    /// The styles for the text of lines at each depth; the last is
+    /// used for any greater depth
+    styles: Vec<Style>,
+}
+
+//ip RatatuiText
+impl<'v, T: ?Sized> RatatuiText<'v, T> {
+    //fp new
+    /// Create a new [RatatuiText] for a value, with unstyled text
+    pub fn new(value: &'v T) -> Self {
+        Self {
+            value,
+            styles: Vec::new(),
+        }
+    }
+
+    //cp with_depth_styles
+    /// Style the text of each line according to its depth; the last
+    /// style is used for all greater depths
+    pub fn with_depth_styles(mut self, styles: Vec<Style>) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    //mp text
+    /// Render the value with the given base indentation string and
+    /// options into a [Text]
+    pub fn text<'a, Opt>(&self, indent: &'a str, options: &'a Opt) -> Text<'static>
+    where
+        Opt: IndentedOptions,
+        T: IndentedDisplay<'a, Opt>,
+    {
+        let recorder = DepthRecorder::default();
+        {
+            let mut ind = Indenter::with_sink(Box::new(recorder.clone()), indent, options);
+            // The recorder cannot fail, so only a formatting error is
+            // possible; the output up to that point is still returned
+            let _ = self.value.indent(&mut ind);
+        }
+        let lines: Vec<Line<'static>> = recorder
+            .take()
+            .into_iter()
+            .map(|(depth, indent, text)| {
+                let style = self
+                    .styles
+                    .get(depth)
+                    .or_else(|| self.styles.last())
+                    .copied()
+                    .unwrap_or_default();
+                Line::from(vec![Span::raw(indent), Span::styled(text, style)])
+            })
+            .collect();
+        Text::from(lines)
+    }
+
+    //mp paragraph
+    /// Render the value with the given base indentation string and
+    /// options into a [Paragraph]
+    pub fn paragraph<'a, Opt>(&self, indent: &'a str, options: &'a Opt) -> Paragraph<'static>
+    where
+        Opt: IndentedOptions,
+        T: IndentedDisplay<'a, Opt>,
+    {
+        Paragraph::new(self.text(indent, options))
+    }
+}
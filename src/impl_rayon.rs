@@ -0,0 +1,135 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    impl_rayon.rs
+@brief   Parallel rendering of slices with rayon
+ */
+
+//a Imports
+use rayon::prelude::*;
+
+use crate::{IndentedDisplay, IndentedOptions, Indenter, RenderedBlock};
+
+//a ParSlice
+//tp ParSlice
+/// An adapter that displays a slice in the same manner as the slice
+/// itself, but renders the elements in parallel using rayon
+///
+/// The slice is split into chunks, each of which is rendered into a
+/// [RenderedBlock] on the rayon thread pool; the blocks are then
+/// output in order through the indenter. This is worthwhile only for
+/// large slices, or elements that are expensive to render.
+///
+/// A slice that may be displayed on a single line (one with fewer
+/// elements than the inline threshold of the options) is displayed
+/// as the slice itself. Each chunk is rendered with its own indenter,
+/// so its elements are displayed as if at the top level (for the
+/// maximum depth of the options, for example).
+#[derive(Debug, Clone, Copy)]
+pub struct ParSlice<'s, T> {
+    /// The slice to display
+    slice: &'s [T],
+    /// The number of elements rendered by each task
+    chunk_size: usize,
+}
+
+//ip ParSlice
+impl<'s, T> ParSlice<'s, T> {
+    //fp new
+    /// Create a new [ParSlice] adapter, rendering 1024 elements in
+    /// each task
+    pub fn new(slice: &'s [T]) -> Self {
+        Self {
+            slice,
+            chunk_size: 1024,
+        }
+    }
+
+    //cp with_chunk_size
+    /// Set the number of elements rendered by each task
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+}
+
+//ip IndentedDisplay for ParSlice
+impl<'a, 's, Opt, T> IndentedDisplay<'a, Opt> for ParSlice<'s, T>
+where
+    Opt: IndentedOptions + Sync,
+    T: IndentedDisplay<'a, Opt> + Sync,
+{
+    //mp indent
+    /// Display the elements as for a slice
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        let options = f.options();
+        let len = self.slice.len();
+        if options.inline_threshold().is_some_and(|n| len < n) {
+            return self.slice.indent(f);
+        }
+        let blocks: Vec<RenderedBlock> = self
+            .slice
+            .par_chunks(self.chunk_size)
+            .enumerate()
+            .map(|(n, chunk)| {
+                let first = n * self.chunk_size;
+                RenderedBlock::render(&Elements { chunk, first, len }, options)
+            })
+            .collect();
+        let (open, close) = options.list_brackets();
+        writeln!(f, "{}", open)?;
+        f.sub().merge(blocks)?;
+        writeln!(f, "{}", close)
+    }
+}
+
+//a Elements
+//ti Elements
+/// The elements of a chunk of a slice, displayed as they are within
+/// the slice when displayed one element per line (without the
+/// brackets)
+struct Elements<'s, T> {
+    /// The elements of the chunk
+    chunk: &'s [T],
+    /// The index within the slice of the first element of the chunk
+    first: usize,
+    /// The number of elements in the whole slice
+    len: usize,
+}
+
+//ii IndentedDisplay for Elements
+impl<'a, 's, Opt, T> IndentedDisplay<'a, Opt> for Elements<'s, T>
+where
+    Opt: IndentedOptions,
+    T: IndentedDisplay<'a, Opt>,
+{
+    fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        let options = f.options();
+        for (i, x) in self.chunk.iter().enumerate() {
+            let i = self.first + i;
+            if options.slice_indices() {
+                write!(f, "{}: ", i)?;
+            }
+            f.value(x)?;
+            if options.trailing_commas() || i + 1 < self.len {
+                writeln!(f, ",")?;
+            } else {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -17,26 +17,65 @@ limitations under the License.
  */
 
 //a Imports
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::fmt::Write;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use crate::IndentedOptions;
+use crate::sinks::{
+    Buffer, DepthRecorder, HeldNewline, IndentSink, IoSink, LineSink, OwnedWriter, Route, Routed,
+    ScopePredicate, SharedTarget, Sink, Tee,
+};
+use crate::text;
+use crate::{
+    DeepFrames, DepthChange, DynOptions, Error, FinalNewline, FlushPolicy, IndentError,
+    IndentStyle, IndentedOptions, Metrics, NullOptions, Overflow, RenderStats, ScopeTiming,
+    Severity,
+};
 
 //a Type aliases
 type IOResult = std::result::Result<(), std::io::Error>;
 type FmtResult = std::result::Result<(), std::fmt::Error>;
 type RrcRoot<'a, Opt> = Rc<RefCell<Root<'a, Opt>>>;
-type RrcInner<'a, Opt> = Rc<RefCell<Inner<'a, Opt>>>;
+type Observer<'a> = Box<dyn FnMut(DepthChange, usize, Option<&str>) + 'a>;
+
+//a Frame
+//ti Frame
+/// The properties of a new indentation frame, as pushed onto the
+/// [Root]; the default is a frame using the base indentation, with no
+/// name
+#[derive(Debug, Default)]
+struct Frame<'a> {
+    /// The indentation string for the frame, if not the base
+    /// indentation
+    ind: Option<Cow<'a, str>>,
+    /// The name of the scope, if it is named
+    name: Option<Cow<'a, str>>,
+    /// Set if all output within the frame is to be discarded
+    muted: bool,
+    /// Set if all output within the frame is to be written raw,
+    /// without indentation
+    verbatim: bool,
+    /// The closing line to write when the frame is popped, if any
+    close: Option<Cow<'a, str>>,
+    /// The severity with which the lines output within the frame are
+    /// tagged, if any
+    severity: Option<Severity>,
+}
 
 //a Root
 //ti Root
 /// The root of the indenter - this is used as an Rc/RefCell
 /// so that it can be accessed by any depth of display node
-struct Root<'a, Opt: IndentedOptions<'a>> {
-    /// The underlying Write object that provides the output method
-    fmt: &'a mut (dyn std::io::Write + 'a),
+struct Root<'a, Opt: IndentedOptions> {
+    /// The underlying sink that provides the output method
+    fmt: Box<dyn Sink + 'a>,
     /// The options the indenter was created with
     options: &'a Opt,
+    /// The stack of options overriding those the indenter was created
+    /// with, and the depth associated with them
+    option_overrides: Vec<(usize, &'a Opt)>,
     /// Set if a newline is pending
     pending_newline: bool,
     /// Boolean set to true if at the start of a line - so if real
@@ -45,59 +84,534 @@ struct Root<'a, Opt: IndentedOptions<'a>> {
     sol: bool,
     /// The basic indentation string to be used per level, unless
     /// explicit per-level indents are provided
-    ind: &'a str,
+    ind: Cow<'a, str>,
+    /// The stack of base indentation strings that replace `ind` for
+    /// the levels from the depth associated with them, as set by
+    /// [Indenter::set_base_indent]
+    bases: Vec<(usize, Cow<'a, str>)>,
     /// The current stack of indentation strings and the depth
     /// associated with them; this is an empty vector if a single
     /// indent string is used.
-    subind: Vec<(usize, &'a str)>,
+    subind: Vec<(usize, Cow<'a, str>)>,
+    /// The current stack of scope names and the depth associated
+    /// with them; only named frames (and labels, which have no depth)
+    /// add to this stack
+    scopes: Vec<(usize, Cow<'a, str>)>,
+    /// The depths at which muted frames were pushed; all output is
+    /// discarded while this is not empty
+    mutes: Vec<usize>,
+    /// The depths at which verbatim frames were pushed; output is
+    /// written without indentation, width limits or padding while
+    /// this is not empty
+    verbatims: Vec<usize>,
+    /// The closing lines of block frames, and the depth associated
+    /// with them; each is written at that depth when its frame is
+    /// popped
+    closers: Vec<(usize, Cow<'a, str>)>,
+    /// The settings of the scope filter for the named scopes to
+    /// which it applies, and the depth associated with them; output
+    /// is discarded if the last of these is off
+    filters: Vec<(usize, bool)>,
+    /// The buffers of output for the named scopes that may be
+    /// summarized (or whose header lines await their durations), the
+    /// depth associated with them, and whether they are summarized
+    buffers: Vec<(usize, Buffer, bool)>,
+    /// The severities with which lines are tagged, and the depth
+    /// associated with them (usize::MAX for writes tagged without a
+    /// frame)
+    severities: Vec<(usize, Severity)>,
+    /// The number of tags of each severity
+    severity_counts: [usize; 3],
+    /// The start times of the named scopes being timed, and the depth
+    /// associated with them
+    timers: Vec<(usize, Instant)>,
+    /// The scope paths and durations of the named scopes that have
+    /// been timed, in the order they completed
+    timings: Vec<(Vec<String>, Duration)>,
+    /// Cleared if all output is to be discarded, independent of the
+    /// frames
+    enabled: bool,
     /// The current depth of indentation
     depth: usize,
+    /// The width in characters of the current line output so far,
+    /// including its indentation
+    column: usize,
+    /// The width of the indentation of the current line, including
+    /// any continuation marker if it is a wrapped line
+    indent_width: usize,
+    /// Set if the current line has been truncated, so the rest of it
+    /// is to be discarded
+    truncated: bool,
+    /// Set if a top-level frame has been popped, so that the current
+    /// top-level block ends with the current line
+    block_ended: bool,
+    /// Set if the text output so far on the current line ends with
+    /// one of the wrap break strings of the options
+    at_break: bool,
+    /// Set if a page break is to be output before any further output
+    pending_page_break: bool,
+    /// Number of lines output on the current page
+    page_lines: usize,
+    /// The depth and indentation of the current line, held back when
+    /// trailing whitespace is trimmed until text that is not
+    /// whitespace is output on the line
+    held_indent: Option<(usize, String)>,
+    /// The whitespace at the end of the text of the current line,
+    /// held back when trailing whitespace is trimmed until text that
+    /// is not whitespace follows it
+    held_space: String,
+    /// The newline that ended the last line of the output, held back
+    /// if the final newline is never to be output
+    held_newline: Option<String>,
+    /// Set once the depth marker has been output for the frame at
+    /// the maximum depth, when deeper frames are elided
+    depth_elided: bool,
+    /// The number of lines started, which is limited by the maximum
+    /// lines of the options
+    lines: usize,
+    /// The number of lines discarded because of the maximum lines of
+    /// the options
+    discarded: usize,
+    /// Set if the current line is being discarded because of the
+    /// maximum lines of the options
+    discarding: bool,
+    /// The statistics of the output written so far
+    stats: RenderStats,
+    /// Set once a line that is not blank has been started
+    started: bool,
+    /// The number of consecutive blank lines output since the last
+    /// line that is not blank
+    blank_lines: usize,
+    /// The most recent failure (such as an error from the underlying
+    /// writer), with the scope path at the point it occurred
+    last_error: Option<Error>,
+    /// Set once the output has been completed, by
+    /// [Indenter::finish] or by the base frame being dropped
+    completed: bool,
+    /// The conditions in which output was abridged as the options
+    /// require (such as truncation), since they were last taken
+    abridgements: Vec<Error>,
+    /// The observer to invoke on every push and pop of a frame, if any
+    observer: Option<Observer<'a>>,
+    /// The routes of output within matching scopes to other sinks
+    routes: Vec<Route<'a>>,
+    /// The depth at which the value being displayed would be output,
+    /// if this is rendering a value only to size it for summarization
+    sizing: Option<usize>,
+    /// The bytes written through [std::io::Write] that are the start
+    /// of a UTF-8 sequence not yet completed
+    utf8_pending: Vec<u8>,
 }
 
 //ii Root
-impl<'a, Opt: IndentedOptions<'a>> Root<'a, Opt> {
+impl<'a, Opt: IndentedOptions> Root<'a, Opt> {
     //fi new
     /// Create a new [Root] of indentation, with a base indent string
-    fn new(fmt: &'a mut (dyn std::io::Write + 'a), ind: &'a str, options: &'a Opt) -> Self {
+    fn new(fmt: Box<dyn Sink + 'a>, ind: Cow<'a, str>, options: &'a Opt) -> Self {
+        let ind = match (options.indent_width(), options.indent_style()) {
+            (Some(n), IndentStyle::Spaces) => " ".repeat(n).into(),
+            (Some(n), IndentStyle::Tabs) => "\t".repeat(n).into(),
+            (None, IndentStyle::Tabs) => "\t".into(),
+            (None, IndentStyle::Spaces) => ind,
+        };
         let subind = Vec::new();
+        let scopes = Vec::new();
         Self {
             fmt,
             options,
+            option_overrides: Vec::new(),
             pending_newline: false,
             sol: true,
             ind,
+            bases: Vec::new(),
             subind,
+            scopes,
+            mutes: Vec::new(),
+            verbatims: Vec::new(),
+            closers: Vec::new(),
+            filters: Vec::new(),
+            buffers: Vec::new(),
+            severities: Vec::new(),
+            severity_counts: [0; 3],
+            timers: Vec::new(),
+            timings: Vec::new(),
+            enabled: true,
             depth: 0,
+            column: 0,
+            indent_width: 0,
+            truncated: false,
+            at_break: false,
+            block_ended: false,
+            pending_page_break: false,
+            page_lines: 0,
+            held_indent: None,
+            held_space: String::new(),
+            held_newline: None,
+            depth_elided: false,
+            lines: 0,
+            discarded: 0,
+            discarding: false,
+            stats: RenderStats::default(),
+            started: false,
+            blank_lines: 0,
+            last_error: None,
+            completed: false,
+            abridgements: Vec::new(),
+            observer: None,
+            routes: Vec::new(),
+            sizing: None,
+            utf8_pending: Vec::new(),
+        }
+    }
+
+    //fi write_bytes
+    /// Write bytes as UTF-8 text, holding back an incomplete sequence
+    /// at the end until the rest of it is written, and replacing any
+    /// invalid sequence with U+FFFD
+    fn write_bytes(&mut self, buf: &[u8]) -> FmtResult {
+        let mut bytes = std::mem::take(&mut self.utf8_pending);
+        bytes.extend_from_slice(buf);
+        let mut rest = &bytes[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(s) => return self.write_str(s),
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    self.write_str(std::str::from_utf8(valid).unwrap())?;
+                    match e.error_len() {
+                        Some(n) => {
+                            self.write_str("\u{FFFD}")?;
+                            rest = &after[n..];
+                        }
+                        None => {
+                            self.utf8_pending = after.to_vec();
+                            return Ok(());
+                        }
+                    }
+                }
+            }
         }
     }
 
     //fi push_indent
     /// Push a new indentation onto the stack - depth is presumably +1
-    /// on the current depth; if the indentation string provided is
-    /// Some then the indentation at this point will use this instead
-    /// of the base indentation
-    fn push_indent(&mut self, depth: usize, ind: Option<&'a str>) {
-        self.pending_newline = true;
-        if let Some(ind) = ind {
+    /// on the current depth; if the frame has an indentation string
+    /// then the indentation at this point will use this instead of
+    /// the base indentation; if it has a name then the scope is named
+    /// for the scope path; if it is muted then output is discarded
+    /// until it is popped
+    fn push_indent(&mut self, depth: usize, frame: Frame<'a>) {
+        if self.options().newline_on_push() {
+            self.pending_newline = true;
+        }
+        if let Some(ind) = frame.ind {
             self.subind.push((self.depth, ind));
         }
+        let named = frame.name.is_some();
+        if let Some(name) = frame.name {
+            self.scopes.push((self.depth, name));
+        }
+        if frame.muted {
+            self.mutes.push(self.depth);
+        }
+        if frame.verbatim {
+            self.verbatims.push(self.depth);
+        }
+        if let Some(close) = frame.close {
+            self.closers.push((self.depth, close));
+        }
+        if let Some(severity) = frame.severity {
+            self.push_severity(self.depth, severity);
+        }
+        if let (true, Some(filter)) = (named, self.options().scope_filter()) {
+            let path: Vec<&str> = self.scopes.iter().map(|(_, n)| n.as_ref()).collect();
+            if let Some(enabled) = filter.enabled(&path) {
+                self.filters.push((self.depth, enabled));
+            }
+        }
+        let timing = self.options().scope_timing();
+        if named && timing != ScopeTiming::Off {
+            self.timers.push((self.depth, Instant::now()));
+        }
+        if named && self.options().summary_lines().is_some() {
+            // Complete the current line now, so that it is not part
+            // of the buffered output of the scope
+            if self.pending_newline {
+                if let Err(e) = self.output_newline() {
+                    self.record_error(e);
+                }
+            }
+            self.buffers.push((self.depth, Buffer::default(), true));
+        } else if named && timing == ScopeTiming::Headers && !self.sol {
+            // The header line is left open, so that the duration may
+            // be appended to it when the scope is popped
+            self.buffers.push((self.depth, Buffer::default(), false));
+        }
         self.depth = depth;
+        if let Some(observer) = &mut self.observer {
+            let name = self
+                .scopes
+                .last()
+                .filter(|_| named)
+                .map(|(_, n)| n.as_ref());
+            observer(DepthChange::Push, depth, name);
+        }
     }
 
     //fi pop_indent
     /// Pop the indent from the stack down to a new depth (which is
     /// presumably self.depth-1)
     ///
-    /// This may involve popping the top of subind, if that is for the
-    /// indentation depth being popped
+    /// This may involve popping the top of subind, scopes, mutes,
+    /// filters and buffers, if those are for the indentation depth
+    /// being popped
+    ///
+    /// The observer, if any, is informed of the pop with the name of
+    /// the scope that was popped
     fn pop_indent(&mut self, depth: usize) {
-        self.pending_newline = true;
+        if self.options().newline_on_pop() {
+            self.pending_newline = true;
+        }
         if let Some((d, _)) = self.subind.last() {
             if *d == depth {
                 self.subind.pop();
             }
         }
+        let mut duration = None;
+        if let Some((d, start)) = self.timers.last() {
+            if *d == depth {
+                let elapsed = start.elapsed();
+                self.timers.pop();
+                self.timings.push((self.scope_path(), elapsed));
+                duration = Some(elapsed);
+            }
+        }
+        let mut name = None;
+        if let Some((d, _)) = self.scopes.last() {
+            if *d == depth {
+                name = self.scopes.pop().map(|(_, n)| n);
+            }
+        }
+        if self.mutes.last() == Some(&depth) {
+            self.mutes.pop();
+        }
+        if self.verbatims.last() == Some(&depth) {
+            self.verbatims.pop();
+        }
+        if let Some((d, _)) = self.severities.last() {
+            if *d == depth {
+                self.severities.pop();
+            }
+        }
+        if let Some((d, _)) = self.filters.last() {
+            if *d == depth {
+                self.filters.pop();
+            }
+        }
+        if let Some((d, _, _)) = self.buffers.last() {
+            if *d == depth {
+                if let Err(e) = self.end_buffer(duration) {
+                    self.record_error(e);
+                }
+            }
+        }
+        if let Some((d, _)) = self.option_overrides.last() {
+            if *d == depth {
+                self.option_overrides.pop();
+            }
+        }
+        while self.bases.last().is_some_and(|(d, _)| *d > depth) {
+            self.bases.pop();
+        }
         self.depth = depth;
+        if self.options().max_depth() == Some(depth) {
+            self.depth_elided = false;
+        }
+        if self.closers.last().is_some_and(|(d, _)| *d == depth) {
+            let (_, close) = self.closers.pop().unwrap();
+            // The closing line is a line of its own, whatever the
+            // options; any error is recorded, as there is no caller
+            // to return it to
+            self.pending_newline = true;
+            let _ = self.write_str(&close);
+            self.pending_newline = true;
+        }
+        // The block ends with the newline after the closing line (if
+        // any), so that the closing line is not left out of it
+        if depth == 0 {
+            self.block_ended = true;
+        }
+        if let Some(observer) = &mut self.observer {
+            observer(DepthChange::Pop, depth, name.as_deref());
+        }
+    }
+
+    //fi push_severity
+    /// Push a severity with which subsequent lines are tagged, and
+    /// count it
+    fn push_severity(&mut self, depth: usize, severity: Severity) {
+        if !self.is_muted() {
+            self.severity_counts[severity.index()] += 1;
+        }
+        self.severities.push((depth, severity));
+    }
+
+    //fi page_break
+    /// Request a page break before any further output
+    ///
+    /// This completes the current line, if it has been started
+    fn page_break(&mut self) {
+        self.pending_newline = true;
+        self.pending_page_break = true;
+    }
+
+    //fi output_page_break
+    /// Output a page break - this must be invoked at the start of a
+    /// line
+    ///
+    /// The page break is the page separator from the options,
+    /// followed (if the options supply one) by a page header line
+    /// that includes the current scope path
+    fn output_page_break(&mut self) -> IOResult {
+        self.pending_page_break = false;
+        self.page_lines = 0;
+        let header = self.options().page_header().map(|header| {
+            let mut header = header.to_string();
+            for (i, (_, name)) in self.scopes.iter().enumerate() {
+                let sep = if i == 0 { ": " } else { " > " };
+                header.push_str(sep);
+                header.push_str(name);
+            }
+            header.push_str(self.line_ending());
+            header
+        });
+        if header.is_some() {
+            self.page_lines = 1;
+        }
+        let separator = self.options().page_separator();
+        self.sink().write_page_break(separator, header.as_deref())
+    }
+
+    //fi sink
+    /// Get the sink that output should be written to - the buffer of
+    /// the innermost scope that may be summarized, if any, otherwise
+    /// the output and the routes active for the current line
+    fn sink(&mut self) -> Routed<'_, 'a> {
+        let hold = self.options().final_newline() == FinalNewline::Never;
+        Routed {
+            buffer: self.buffers.last_mut().map(|(_, buffer, _)| buffer),
+            fmt: HeldNewline {
+                sink: self.fmt.as_mut(),
+                stats: &mut self.stats,
+                held: &mut self.held_newline,
+                hold,
+            },
+            routes: &mut self.routes,
+        }
+    }
+
+    //fi end_block
+    /// Invoke the end of a block on the output, first writing any
+    /// newline held back from it
+    fn end_block(&mut self) -> IOResult {
+        self.sink().fmt.end_block()?;
+        if self.options().flush_policy() == FlushPolicy::EachBlock {
+            self.sink().flush()?;
+        }
+        Ok(())
+    }
+
+    //fi route_line
+    /// Determine the routes for a line starting at the current scope
+    /// path
+    fn route_line(&mut self) {
+        if !self.routes.is_empty() {
+            let path = self.scope_path();
+            Route::set_path(&mut self.routes, &path);
+        }
+    }
+
+    //fi end_buffer
+    /// End the buffering of the innermost buffered scope
+    ///
+    /// If the scope is not summarized then its header line is still
+    /// open, and the duration of the scope (if it was timed) is
+    /// appended to it before the output of the scope is passed on
+    ///
+    /// If the scope is summarized, and it output more than the
+    /// permitted number of lines, then its output is replaced by a
+    /// single summary line at the depth of the scope; otherwise it is
+    /// passed on unchanged
+    fn end_buffer(&mut self, duration: Option<Duration>) -> IOResult {
+        let (_, buffer, summarize) = self.buffers.pop().unwrap();
+        if !summarize {
+            if let (Some(duration), false) = (duration, self.is_muted()) {
+                let marker = self
+                    .options()
+                    .timing_marker()
+                    .replace("{t}", &format!("{:?}", duration));
+                self.sink().write_text(&marker)?;
+            }
+            self.route_line();
+            return buffer.replay(&mut self.sink());
+        }
+        let max = self.options().summary_lines().unwrap_or(usize::MAX);
+        if buffer.lines() <= max {
+            self.route_line();
+            return buffer.replay(&mut self.sink());
+        }
+        let summary = self
+            .options()
+            .summary_marker()
+            .replace("{n}", &buffer.lines().to_string())
+            .replace("{depth}", &self.depth.to_string());
+        // The output of the scope is discarded, so the summary starts
+        // a line of its own
+        self.sol = true;
+        self.truncated = false;
+        self.output_indent()?;
+        self.sol = false;
+        self.output_text(&summary)?;
+        self.output_newline()
+    }
+
+    //fi options
+    /// Get the options in effect - those of the innermost frame that
+    /// overrides them, or those the indenter was created with
+    fn options(&self) -> &'a Opt {
+        match self.option_overrides.last() {
+            Some((_, options)) => options,
+            None => self.options,
+        }
+    }
+
+    //fi is_muted
+    /// Return true if output is currently being discarded
+    fn is_muted(&self) -> bool {
+        !self.enabled || !self.mutes.is_empty() || matches!(self.filters.last(), Some((_, false)))
+    }
+
+    //fi scope_path
+    /// Get the current scope path - the names of the enclosing named
+    /// scopes, outermost first
+    fn scope_path(&self) -> Vec<String> {
+        self.scopes.iter().map(|(_, n)| n.to_string()).collect()
+    }
+
+    //fi record_error
+    /// Record an error from the underlying writer, with the current
+    /// scope path, and convert it to a [std::fmt::Error]
+    fn record_error<K: Into<IndentError>>(&mut self, kind: K) -> std::fmt::Error {
+        self.last_error = Some(Error::new(self.scope_path(), kind));
+        std::fmt::Error
+    }
+
+    //fi record_abridgement
+    /// Record a condition in which the output was abridged as the
+    /// options require, with the current scope path
+    fn record_abridgement(&mut self, kind: IndentError) {
+        let error = Error::new(self.scope_path(), kind);
+        self.abridgements.push(error);
     }
 
     //fi output_newline
@@ -108,12 +622,53 @@ impl<'a, Opt: IndentedOptions<'a>> Root<'a, Opt> {
     /// will require the appropriate indent
     fn output_newline(&mut self) -> IOResult {
         self.pending_newline = false;
+        self.discarding = false;
         if self.sol {
             Ok(())
         } else {
+            self.pad_line()?;
             self.sol = true;
-            self.fmt.write_all(b"\n")
+            self.column = 0;
+            self.truncated = false;
+            self.page_lines += 1;
+            self.held_space.clear();
+            if let Some((depth, _)) = self.held_indent.take() {
+                // The line is only whitespace, so it is blank
+                self.sink().write_indent(depth, "")?;
+            }
+            let ending = self.line_ending();
+            self.sink().write_newline(ending)?;
+            if self.options().flush_policy() == FlushPolicy::EachLine {
+                self.sink().flush()?;
+            }
+            if self.depth == 0 && self.block_ended {
+                self.block_ended = false;
+                self.end_block()?;
+            }
+            Ok(())
+        }
+    }
+
+    //fi line_ending
+    /// Get the line ending to use, from the options
+    fn line_ending(&self) -> &'a str {
+        self.options().line_ending()
+    }
+
+    //fi pad_line
+    /// Pad the current line with spaces to the pad width of the
+    /// options, if it has one
+    fn pad_line(&mut self) -> IOResult {
+        if !self.verbatims.is_empty() {
+            return Ok(());
+        }
+        if let Some(width) = self.options().pad_width() {
+            if self.column < width {
+                let padding = " ".repeat(width - self.column);
+                self.output_text(&padding)?;
+            }
         }
+        Ok(())
     }
 
     //fi output_indent
@@ -123,21 +678,146 @@ impl<'a, Opt: IndentedOptions<'a>> Root<'a, Opt> {
     /// hence `sol` is set, and any characters to output afterwards
     /// will require the appropriate indent
     fn output_indent(&mut self) -> IOResult {
+        self.lines += 1;
+        self.started = true;
+        self.blank_lines = 0;
+        let depth = self.depth;
+        let verbatim = !self.verbatims.is_empty();
+        let indent = if verbatim {
+            String::new()
+        } else {
+            self.indent_string()
+        };
+        self.indent_width = text::width(&indent);
+        self.column = self.indent_width;
+        self.route_line();
+        if self.trimming() {
+            self.held_indent = Some((depth, indent));
+        } else {
+            self.sink().write_indent(depth, &indent)?;
+        }
+        if verbatim {
+            return Ok(());
+        }
+        if let Some((_, severity)) = self.severities.last() {
+            let marker = self.options().severity_marker(*severity);
+            self.output_text(marker)?;
+        }
+        Ok(())
+    }
+
+    //fi discard_line
+    /// Determine if a line about to be started is to be discarded
+    /// because the maximum lines of the options have been output; if
+    /// so, count it as discarded
+    fn discard_line(&mut self) -> bool {
+        let Some(max) = self.options().max_lines() else {
+            return false;
+        };
+        if self.lines < max {
+            return false;
+        }
+        if self.discarded == 0 {
+            self.record_abridgement(IndentError::LinesExceeded { max });
+        }
+        self.discarded += 1;
+        self.discarding = true;
+        true
+    }
+
+    //fi output_blank_line
+    /// Output a blank line - this must be invoked at the start of a
+    /// line
+    fn output_blank_line(&mut self) -> IOResult {
+        if self.discard_line() {
+            return Ok(());
+        }
+        self.lines += 1;
+        let depth = self.depth;
+        let options = self.options();
+        let indented = options.indent_blank_lines() && !options.trim_trailing_whitespace();
+        let indent = if indented && self.verbatims.is_empty() {
+            self.indent_string()
+        } else {
+            String::new()
+        };
+        self.route_line();
+        self.sink().write_indent(depth, &indent)?;
+        let ending = self.line_ending();
+        self.sink().write_newline(ending)?;
+        self.page_lines += 1;
+        self.blank_lines += 1;
+        Ok(())
+    }
+
+    //fi elide_depth
+    /// Discard output deeper than the maximum depth, outputting the
+    /// depth marker in its place on a line of its own, if it has not
+    /// already been output for the frame at the maximum depth
+    fn elide_depth(&mut self, max: usize) -> FmtResult {
+        if self.depth_elided {
+            return Ok(());
+        }
+        self.depth_elided = true;
+        let depth = self.depth;
+        self.record_abridgement(IndentError::DepthExceeded { depth, max });
+        self.depth = max + 1;
+        let marker = self.options().depth_marker();
+        let result = self.output_str(marker);
+        self.pending_newline = true;
+        self.depth = depth;
+        result.map_err(|e| self.record_error(e))
+    }
+
+    //fi ensure_blank_line
+    /// Complete the current line, if it has been started, and output a
+    /// blank line unless the last line output was blank (or nothing
+    /// has yet been output)
+    fn ensure_blank_line(&mut self) -> FmtResult {
+        if self.is_muted() {
+            return Ok(());
+        }
+        if self.pending_newline || !self.sol {
+            if let Err(e) = self.output_newline() {
+                return Err(self.record_error(e));
+            }
+        }
+        if self.started && self.blank_lines == 0 {
+            if let Err(e) = self.output_blank_line() {
+                return Err(self.record_error(e));
+            }
+        }
+        Ok(())
+    }
+
+    //fi indent_string
+    /// Get the indentation string for the current depth
+    fn indent_string(&self) -> String {
         let sublen = self.subind.len();
         let mut s = 0;
-        for i in 0..self.depth {
-            if s < sublen {
-                if self.subind[s].0 == i {
-                    self.fmt.write_all(self.subind[s].1.as_bytes())?;
-                    s += 1;
-                } else {
-                    self.fmt.write_all(self.ind.as_bytes())?;
-                }
+        let mut indent = String::new();
+        let depth = match self.options().max_depth() {
+            Some(max) if self.options().deep_frames() == DeepFrames::Flatten => self.depth.min(max),
+            _ => self.depth,
+        };
+        for i in 0..depth {
+            if s < sublen && self.subind[s].0 == i {
+                indent.push_str(&self.subind[s].1);
+                s += 1;
             } else {
-                self.fmt.write_all(self.ind.as_bytes())?;
+                indent.push_str(self.base_indent(i));
             }
         }
-        Ok(())
+        indent
+    }
+
+    //fi base_indent
+    /// Get the base indentation string for a level of indentation
+    fn base_indent(&self, level: usize) -> &str {
+        match self.bases.iter().rev().find(|(d, _)| *d <= level) {
+            Some((_, ind)) => ind,
+            None => &self.ind,
+        }
     }
 
     //fi output_str
@@ -158,29 +838,255 @@ impl<'a, Opt: IndentedOptions<'a>> Root<'a, Opt> {
         if self.pending_newline {
             self.output_newline()?;
         }
+        if self.discarding || (self.sol && self.discard_line()) {
+            return Ok(());
+        }
+        if self.truncated {
+            return Ok(());
+        }
+        self.start_line()?;
+        match self.options().max_width() {
+            Some(width) if self.verbatims.is_empty() => self.output_text_width(s, width),
+            _ => self.output_text(s),
+        }
+    }
+
+    //fi start_line
+    /// Start a line of output if at the start of a line, with a page
+    /// break first if required, and then the indentation
+    fn start_line(&mut self) -> IOResult {
         if self.sol {
+            if let Some(n) = self.options().page_length() {
+                if self.page_lines >= n {
+                    self.pending_page_break = true;
+                }
+            }
+            if self.pending_page_break {
+                self.output_page_break()?;
+            }
             self.output_indent()?;
         }
         self.sol = false;
-        self.fmt.write_all(s.as_bytes())
+        Ok(())
+    }
+
+    //fi output_text
+    /// Output text (with no newlines) within the current line
+    fn output_text(&mut self, s: &str) -> IOResult {
+        if !s.is_empty() {
+            self.at_break = self.options().wrap_breaks().iter().any(|b| s.ends_with(b));
+        }
+        self.column += text::width(s);
+        if !self.trimming() {
+            return self.sink().write_text(s);
+        }
+        let content = s.trim_end();
+        if content.is_empty() {
+            self.held_space.push_str(s);
+            return Ok(());
+        }
+        if let Some((depth, indent)) = self.held_indent.take() {
+            self.sink().write_indent(depth, &indent)?;
+        }
+        if !self.held_space.is_empty() {
+            let space = std::mem::take(&mut self.held_space);
+            self.sink().write_text(&space)?;
+        }
+        self.held_space.push_str(&s[content.len()..]);
+        self.sink().write_text(content)
+    }
+
+    //fi trimming
+    /// Return true if trailing whitespace is to be trimmed from the
+    /// current line
+    fn trimming(&self) -> bool {
+        self.verbatims.is_empty() && self.options().trim_trailing_whitespace()
+    }
+
+    //fi output_text_width
+    /// Output text (with no newlines) within the current line, which
+    /// must not exceed the given width; if it would, then the
+    /// overflow policy of the options applies
+    fn output_text_width(&mut self, mut s: &str, width: usize) -> IOResult {
+        loop {
+            let room = width.saturating_sub(self.column);
+            if text::width(s) <= room {
+                return self.output_text(s);
+            }
+            match self.options().overflow() {
+                Overflow::Error => {
+                    if self.last_error.is_none() {
+                        self.record_error(IndentError::WidthExceeded {
+                            width: self.column + text::width(s),
+                            max: width,
+                        });
+                    }
+                    return self.output_text(s);
+                }
+                Overflow::Truncate => {
+                    self.record_abridgement(IndentError::Truncated {
+                        width: self.column + text::width(s),
+                        max: width,
+                    });
+                    let marker = self.options().truncation_marker();
+                    let keep = room.saturating_sub(text::width(marker));
+                    self.output_text(text::split_at_width(s, keep).0)?;
+                    self.output_text(marker)?;
+                    self.truncated = true;
+                    return Ok(());
+                }
+                Overflow::Wrap => {
+                    // At least one character must be output on each
+                    // line, even if the indentation fills it
+                    let end_marker = self.options().wrap_end_marker();
+                    let room = room.saturating_sub(text::width(end_marker));
+                    let keep = if room == 0 && self.column == self.indent_width {
+                        1
+                    } else {
+                        room
+                    };
+                    let (line, rest) = self.split_for_wrap(s, keep);
+                    self.output_text(line)?;
+                    self.output_text(end_marker)?;
+                    self.output_newline()?;
+                    self.start_line()?;
+                    self.output_text(self.options().wrap_start_marker())?;
+                    self.indent_width = self.column;
+                    s = rest;
+                }
+            }
+        }
+    }
+
+    //fi split_for_wrap
+    /// Split text to be wrapped, with at most `n` characters before
+    /// the split
+    ///
+    /// The split is after the last wrap break string of the options
+    /// that fits, if there is one; otherwise, if the line so far ends
+    /// with a break string, nothing is put on this line; otherwise
+    /// the split is after `n` characters
+    fn split_for_wrap<'s>(&self, s: &'s str, n: usize) -> (&'s str, &'s str) {
+        let (line, rest) = text::split_at_width(s, n);
+        let breaks = self.options().wrap_breaks();
+        let split = breaks
+            .iter()
+            .filter_map(|b| line.rfind(b).map(|i| i + b.len()))
+            .max();
+        match split {
+            Some(i) => s.split_at(i),
+            None if self.at_break && self.column > self.indent_width => ("", s),
+            None => (line, rest),
+        }
     }
 
     //fi complete
-    /// Invoked by the last stack frame being dropped; tidy up the
-    /// output
+    /// Invoked by [Indenter::finish] or by the last stack frame being
+    /// dropped; tidy up the output, if it has not already been
+    /// completed
     ///
-    /// No errors can be returned (this is in Drop)
-    fn complete(&mut self) {
-        if self.pending_newline {
-            let _ = self.output_newline();
+    /// Every step is attempted even if an earlier one fails; the
+    /// first error is returned
+    fn complete(&mut self) -> IOResult {
+        if self.completed {
+            return Ok(());
+        }
+        self.completed = true;
+        if !self.utf8_pending.is_empty() {
+            self.utf8_pending.clear();
+            let _ = self.write_str("\u{FFFD}");
+        }
+        let mut results = vec![];
+        let final_newline = self.options().final_newline();
+        // The block is ended when the output is complete, below
+        self.block_ended = false;
+        if self.pending_newline || (final_newline == FinalNewline::Always && !self.sol) {
+            results.push(self.output_newline());
+        } else if !self.sol {
+            results.push(self.pad_line());
+        }
+        while !self.buffers.is_empty() {
+            results.push(self.end_buffer(None));
+        }
+        if self.discarded > 0 {
+            let n = self.discarded.to_string();
+            let line = self.options().lines_marker().replace("{n}", &n);
+            results.push(self.output_base_line(&line));
+        }
+        if self.options().severity_summary() {
+            results.push(self.output_severity_summary());
+        }
+        if self.options().scope_timing() == ScopeTiming::Summary {
+            results.push(self.output_timings());
+        }
+        // The held newline is the final newline, so it is discarded
+        self.held_newline = None;
+        results.push(self.fmt.end_block());
+        if self.options().flush_policy() != FlushPolicy::Manual {
+            results.push(self.sink().flush());
+        }
+        results.into_iter().collect()
+    }
+
+    //fi output_timings
+    /// Output the summary of the durations of the named scopes, one
+    /// line per scope at the base depth; this must be invoked at the
+    /// start of a line
+    fn output_timings(&mut self) -> IOResult {
+        let timings = std::mem::take(&mut self.timings);
+        for (path, duration) in &timings {
+            let line = format!("{}: {:?}", path.join(" > "), duration);
+            self.output_base_line(&line)?;
+        }
+        self.timings = timings;
+        Ok(())
+    }
+
+    //fi output_severity_summary
+    /// Output the summary of the number of tags of each severity, if
+    /// there were any, such as "3 errors, 7 warnings"; this must be
+    /// invoked at the start of a line
+    fn output_severity_summary(&mut self) -> IOResult {
+        let nouns = [
+            ("info", "info"),
+            ("warning", "warnings"),
+            ("error", "errors"),
+        ];
+        let counts: Vec<String> = (0..3)
+            .rev()
+            .filter(|i| self.severity_counts[*i] > 0)
+            .map(|i| {
+                let n = self.severity_counts[i];
+                let noun = if n == 1 { nouns[i].0 } else { nouns[i].1 };
+                format!("{} {}", n, noun)
+            })
+            .collect();
+        if counts.is_empty() {
+            return Ok(());
+        }
+        self.output_base_line(&counts.join(", "))
+    }
+
+    //fi output_base_line
+    /// Output a complete line of text at the base depth, without any
+    /// indentation or tagging, unless output is discarded; this must
+    /// be invoked at the start of a line
+    fn output_base_line(&mut self, line: &str) -> IOResult {
+        if self.is_muted() {
+            return Ok(());
         }
+        self.route_line();
+        self.sink().write_indent(0, "")?;
+        self.sink().write_text(line)?;
+        let ending = self.line_ending();
+        self.sink().write_newline(ending)
     }
 
     //zz All done
 }
 
 //ii Debug for Root
-impl<'a, Opt: IndentedOptions<'a>> std::fmt::Debug for Root<'a, Opt> {
+impl<'a, Opt: IndentedOptions> std::fmt::Debug for Root<'a, Opt> {
     //fp fmt
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
@@ -192,7 +1098,7 @@ impl<'a, Opt: IndentedOptions<'a>> std::fmt::Debug for Root<'a, Opt> {
 }
 
 //ii Write for Root
-impl<'a, Opt: IndentedOptions<'a>> std::fmt::Write for Root<'a, Opt> {
+impl<'a, Opt: IndentedOptions> std::fmt::Write for Root<'a, Opt> {
     //fp write_str
     /// Perform the actual write operation, providing a write!
     /// capability (etc) for Root
@@ -202,16 +1108,38 @@ impl<'a, Opt: IndentedOptions<'a>> std::fmt::Write for Root<'a, Opt> {
     ///
     /// output_newline is invoked *between* every line of output
     /// i.e. for every newline character in the input string
+    ///
+    /// Any error from the underlying writer is recorded so that it
+    /// may be retrieved with [Indenter::last_error]
+    ///
+    /// If the output is muted then the string is discarded
     fn write_str(&mut self, s: &str) -> FmtResult {
+        if self.is_muted() {
+            return Ok(());
+        }
+        if let Some(max) = self.options().max_depth().filter(|max| self.depth > *max) {
+            if self.options().deep_frames() == DeepFrames::Elide {
+                return self.elide_depth(max);
+            }
+        }
         let mut output_newline = false;
         for line in s.split('\n') {
             if output_newline {
-                if self.output_newline().is_err() {
-                    return Err(std::fmt::Error);
+                // A newline at the start of a line is a blank line
+                let blank = self.sol && !self.pending_newline && !self.discarding;
+                let result = if !blank {
+                    self.output_newline()
+                } else if self.blank_lines < self.options().max_blank_lines() {
+                    self.output_blank_line()
+                } else {
+                    Ok(())
+                };
+                if let Err(e) = result {
+                    return Err(self.record_error(e));
                 }
             }
-            if self.output_str(line).is_err() {
-                return Err(std::fmt::Error);
+            if let Err(e) = self.output_str(line) {
+                return Err(self.record_error(e));
             }
             output_newline = true;
         }
@@ -219,121 +1147,228 @@ impl<'a, Opt: IndentedOptions<'a>> std::fmt::Write for Root<'a, Opt> {
     }
 }
 
-//a Inner
-//ti Inner
-/// This represents an indentation 'stack frame', including the uppermost stack frame.
-///
-/// All the stack frames for the same indenter refer to the same
-/// [Root], which is created by the uppermost stack frame creation.
+//a Indenter
+//tp Indenter
+/// The public face of the library, this is the type that must be
+/// created to use the [crate::IndentedDisplay] trait
 ///
-/// When a subframe is created it clones the [Root], and sets its parent appropriately
+/// This utilizes a [std::fmt::Write] formatter as its output, a base
+/// indent string that is used for all levels of indentation (unless
+/// overridden individually by indentation frames), and an options
+/// structure that contains options that may be interrogated by the
+/// implementation of [crate::IndentedDisplay]; the options default
+/// to [NullOptions]
 ///
-/// When the subframe is dropped it informs the [Root] and resets the
-/// state back to that of its parent.
-#[derive(Debug)]
-pub struct Inner<'a, Opt: IndentedOptions<'a>> {
+/// All the frames of an indenter share the same root, which holds
+/// the stacks of frame properties; a frame itself is simply its
+/// depth within those stacks, so creating one does not allocate.
+pub struct Indenter<'a, Opt: IndentedOptions = NullOptions> {
+    /// The root shared by all the frames of the indenter
     root: RrcRoot<'a, Opt>,
-    parent: Option<RrcInner<'a, Opt>>,
+    /// The depth of this frame
     depth: usize,
+    /// The depth of the frame this was created from, to be restored
+    /// when this is dropped; None for the base [Indenter]
+    parent: Option<usize>,
 }
 
-//ii Drop for Inner
-impl<'a, Opt: IndentedOptions<'a>> Drop for Inner<'a, Opt> {
+//ip Drop for Indenter
+impl<'a, Opt: IndentedOptions> Drop for Indenter<'a, Opt> {
     //fi drop
-    /// Invoked automatically by Rust when the stack frame goes out of
-    /// scope allowing the indentation to revert to that prior to the
-    /// creation of this stack frame
+    /// Invoked automatically by Rust when the frame goes out of scope
+    /// allowing the indentation to revert to that prior to the
+    /// creation of this frame; dropping the base [Indenter] completes
+    /// the output
     fn drop(&mut self) {
-        if let Some(parent) = &self.parent {
-            let depth = parent.borrow().depth;
-            self.root.borrow_mut().pop_indent(depth);
-        } else {
-            self.root.borrow_mut().complete();
+        let mut root = self.root.borrow_mut();
+        match self.parent {
+            Some(parent) => {
+                for depth in (parent..self.depth).rev() {
+                    root.pop_indent(depth);
+                }
+            }
+            None => {
+                let _ = root.complete();
+            }
         }
     }
 }
 
-//ii Inner
-impl<'a, Opt: IndentedOptions<'a>> Inner<'a, Opt> {
-    //fi root
-    /// Create a root stack frame - which has no parent and is at depth 0
-    fn root(root: RrcRoot<'a, Opt>) -> RrcInner<'a, Opt> {
-        Rc::new(RefCell::new(Self {
-            root: root,
-            parent: None,
-            depth: 0,
-        }))
-    }
-
-    //fi subnode
-    /// Create a subnode of this stack frame, with an optional
-    /// depth-specific indentation string
-    fn subnode(s: &Rc<RefCell<Self>>, ind: Option<&'a str>) -> RrcInner<'a, Opt> {
-        let root = s.borrow().root.clone();
-        let parent = Some(s.clone());
-        let depth = s.borrow().depth + 1;
-        root.borrow_mut().push_indent(depth, ind);
-        Rc::new(RefCell::new(Self {
-            root,
-            parent,
-            depth,
-        }))
+//ip Indenter
+impl<'a, Opt: IndentedOptions> Indenter<'a, Opt> {
+    //fp new
+    /// Create a new [Indenter], to be used with types that implement
+    /// the [crate::IndentedDisplay] trait; this specifies the formatter, the
+    /// base indentation string, and the options for the indentation
+    ///
+    /// The indentation string may be borrowed or owned, so it may be
+    /// computed at runtime
+    pub fn new<S: Into<Cow<'a, str>>>(
+        fmt: &'a mut (dyn std::io::Write + 'a),
+        s: S,
+        options: &'a Opt,
+    ) -> Self {
+        Self::for_sink(fmt, s, options)
     }
 
-    //fi pop
-    /// Create a subnode of this stack frame, with an optional
-    /// depth-specific indentation string
-    fn pop(self) -> Option<RrcInner<'a, Opt>> {
-        if let Some(parent) = &self.parent {
-            Some(parent.clone())
-        } else {
-            None
-        }
+    //fp for_sink
+    /// Create a new [Indenter] as for [Indenter::new], writing to any
+    /// [IndentSink] - such as a `String`, a [std::fmt::Formatter], a
+    /// `Vec<u8>`, a file or stdout
+    pub fn for_sink<T, S>(sink: T, s: S, options: &'a Opt) -> Self
+    where
+        T: IndentSink<'a>,
+        S: Into<Cow<'a, str>>,
+    {
+        Self::with_sink(sink.into_sink().0, s, options)
     }
 
-    //fi take_parent
-    /// Provided this [Inner] has a parent (panic otherwise), return
-    /// that parent by deconstructing this
+    //fp new_fmt
+    /// Create a new [Indenter] as for [Indenter::new], writing to a
+    /// [std::fmt::Write] (such as a `String` or a
+    /// [std::fmt::Formatter]) rather than to a [std::io::Write]
+    pub fn new_fmt<S: Into<Cow<'a, str>>>(
+        fmt: &'a mut (dyn std::fmt::Write + 'a),
+        s: S,
+        options: &'a Opt,
+    ) -> Self {
+        Self::for_sink(fmt, s, options)
+    }
+
+    //fp for_lines
+    /// Create a new [Indenter] that delivers each completed line of
+    /// its output, fully indented but without its line ending, to a
+    /// callback - such as a GUI log pane or a test harness
     ///
-    /// This node must not be borrowed elsewhere for this to work,
-    /// as it cannot be deconstructed if so
-    fn take_parent(s: Rc<RefCell<Self>>) -> RrcInner<'a, Opt> {
-        assert!(s.borrow().parent.is_some());
-        match Rc::try_unwrap(s) {
-            Err(_) => {
-                panic!("Indent was multiply borrowed");
-            }
-            Ok(x) => x.into_inner().pop().unwrap(),
+    /// The lines of page breaks are delivered as lines; a final line
+    /// that is not ended is delivered when the output is complete.
+    pub fn for_lines<F, S>(callback: F, s: S, options: &'a Opt) -> Self
+    where
+        F: FnMut(&str) + 'a,
+        S: Into<Cow<'a, str>>,
+    {
+        Self::with_sink(Box::new(LineSink::new(callback)), s, options)
+    }
+
+    //fp new_owned
+    /// Create a new [OwnedIndenter], which takes ownership of the
+    /// writer, with the base indentation string and options as for
+    /// [Indenter::new]
+    ///
+    /// The [OwnedIndenter] dereferences to an [Indenter]; the writer
+    /// is returned by [OwnedIndenter::into_inner]
+    pub fn new_owned<W, S>(writer: W, s: S, options: &'a Opt) -> OwnedIndenter<'a, W, Opt>
+    where
+        W: std::io::Write + 'a,
+        S: Into<Cow<'a, str>>,
+    {
+        let writer = OwnedWriter::new(writer);
+        let indenter = Self::with_sink(Box::new(IoSink::new(writer.clone())), s, options);
+        OwnedIndenter { indenter, writer }
+    }
+
+    //fp measure
+    /// Measure the output of a value, with the base indentation
+    /// string and options as for [Indenter::new], without writing it
+    ///
+    /// This returns the number of lines, the width of the widest
+    /// line, and the greatest depth of any line - so that (for
+    /// example) a layout may be chosen before the value is displayed
+    pub fn measure<T, S>(x: &T, s: S, options: &'a Opt) -> Metrics
+    where
+        T: crate::IndentedDisplay<'a, Opt> + ?Sized,
+        S: Into<Cow<'a, str>>,
+    {
+        let recorder = DepthRecorder::default();
+        {
+            let mut ind = Self::with_sink(Box::new(recorder.clone()), s, options);
+            let _ = x.indent(&mut ind);
+        }
+        let lines = recorder.take();
+        let width =
+            |(_, indent, text): &(usize, String, String)| text::width(indent) + text::width(text);
+        Metrics {
+            lines: lines.len(),
+            width: lines.iter().map(width).max().unwrap_or(0),
+            depth: lines.iter().map(|(depth, _, _)| *depth).max().unwrap_or(0),
         }
     }
 
-    //zz All done
-}
+    //fp with_char
+    /// Create a new [Indenter] as for [Indenter::new], with the base
+    /// indentation string being a character repeated a number of
+    /// times, such as four spaces
+    pub fn with_char(
+        fmt: &'a mut (dyn std::io::Write + 'a),
+        c: char,
+        n: usize,
+        options: &'a Opt,
+    ) -> Self {
+        Self::new(fmt, repeat_char(c, n), options)
+    }
 
-//a Indenter
-//tp Indenter
-/// The public face of the library, this is the type that must be
-/// created to use the [crate::IndentedDisplay] trait
-///
-/// This utilizes a [std::fmt::Write] formatter as its output, a base
-/// indent string that is used for all levels of indentation (unless
-/// overridden individually by indentation frames), and an options
-/// structure that contains options that may be interrogated by the
-/// implementation of [crate::IndentedDisplay]
-pub struct Indenter<'a, Opt: IndentedOptions<'a>> {
-    node: RrcInner<'a, Opt>,
-}
+    //fp stdout
+    /// Create a new [Indenter] that writes to stdout, holding the lock
+    /// on stdout for the lifetime of the indenter
+    ///
+    /// Output from other threads (such as with `println!`) therefore
+    /// cannot be interleaved with the indented output; such threads
+    /// block until the indenter is dropped
+    pub fn stdout<S: Into<Cow<'a, str>>>(s: S, options: &'a Opt) -> Self {
+        Self::for_sink(std::io::stdout(), s, options)
+    }
 
-//ip Indenter
-impl<'a, Opt: IndentedOptions<'a>> Indenter<'a, Opt> {
-    //fp new
-    /// Create a new [Indenter], to be used with types that implement
-    /// the [crate::IndentedDisplay] trait; this specifies the formatter, the
-    /// base indentation string, and the options for the indentation
-    pub fn new(fmt: &'a mut (dyn std::io::Write + 'a), s: &'a str, options: &'a Opt) -> Self {
-        let r = Rc::new(RefCell::new(Root::new(fmt, s, options)));
-        let node = Inner::root(r);
-        Self { node }
+    //fp shared
+    /// Create a new [Indenter] that writes to a [SharedTarget], which
+    /// may be shared with indenters on other threads
+    ///
+    /// The output is buffered and written to the target atomically
+    /// when the first line at the base depth after a top-level frame
+    /// is popped is completed (such as the closing line of a block),
+    /// and when the indenter is dropped; so each top-level block of
+    /// output, with all of its nested output, is never interleaved
+    /// with the output of other indenters
+    pub fn shared<S: Into<Cow<'a, str>>>(target: &SharedTarget, s: S, options: &'a Opt) -> Self {
+        Self::for_sink(target, s, options)
+    }
+
+    //fi with_sink
+    /// Create a new [Indenter] with a boxed sink for its output
+    pub(crate) fn with_sink<S: Into<Cow<'a, str>>>(
+        fmt: Box<dyn Sink + 'a>,
+        s: S,
+        options: &'a Opt,
+    ) -> Self {
+        let root = Rc::new(RefCell::new(Root::new(fmt, s.into(), options)));
+        Self {
+            root,
+            depth: 0,
+            parent: None,
+        }
+    }
+
+    //fi subframe
+    /// Create a new subframe of the [Indenter] with the given frame
+    /// properties, mutably borrowing this frame for its lifetime
+    fn subframe(&mut self, frame: Frame<'a>) -> SubIndenter<'_, 'a, Opt> {
+        self.root.borrow_mut().push_indent(self.depth + 1, frame);
+        self.levels_deeper(1)
+    }
+
+    //fi levels_deeper
+    /// Create a subframe of the [Indenter] for frames that have been
+    /// pushed a number of levels deeper than it, which are popped
+    /// when the subframe is dropped
+    fn levels_deeper(&mut self, n: usize) -> SubIndenter<'_, 'a, Opt> {
+        let indenter = Self {
+            root: self.root.clone(),
+            depth: self.depth + n,
+            parent: Some(self.depth),
+        };
+        SubIndenter {
+            indenter,
+            parent: self,
+        }
     }
 
     //fp sub
@@ -343,9 +1378,176 @@ impl<'a, Opt: IndentedOptions<'a>> Indenter<'a, Opt> {
     /// to create subframes of indentation. The subframe is removed
     /// from the indentation output stack when it is *dropped*, so it
     /// must either go out of scope or be explicitly dropped.
-    pub fn sub(&self) -> Self {
-        let node = Inner::subnode(&self.node, None);
-        Self { node }
+    ///
+    /// The subframe mutably borrows this frame until it is dropped,
+    /// so this frame cannot be written to while the subframe is
+    /// alive.
+    pub fn sub(&mut self) -> SubIndenter<'_, 'a, Opt> {
+        self.subframe(Frame::default())
+    }
+
+    //fi detached
+    /// Create a subframe of the [Indenter] that does not borrow it,
+    /// with an optional indentation string; the caller must drop it
+    /// before writing to this frame again, and before dropping this
+    #[cfg(feature = "futures-io")]
+    pub(crate) fn detached(&self, ind: Option<Cow<'a, str>>) -> Self {
+        let frame = Frame {
+            ind,
+            ..Default::default()
+        };
+        self.root.borrow_mut().push_indent(self.depth + 1, frame);
+        Self {
+            root: self.root.clone(),
+            depth: self.depth + 1,
+            parent: Some(self.depth),
+        }
+    }
+
+    //fp sub_n
+    /// Create a new subframe of the [Indenter] that is a number of
+    /// levels deeper, each using the base indentation, as a single
+    /// frame; all of the levels are removed when it is dropped
+    ///
+    /// This is useful when the depth of nesting is determined
+    /// externally (such as by a parser). [SubIndenter::pop] on the
+    /// subframe returns this frame; zero levels returns another
+    /// handle on this frame.
+    pub fn sub_n(&mut self, n: usize) -> SubIndenter<'_, 'a, Opt> {
+        for depth in self.depth + 1..=self.depth + n {
+            self.root.borrow_mut().push_indent(depth, Frame::default());
+        }
+        self.levels_deeper(n)
+    }
+
+    //fp sub_named
+    /// Create a new subframe of the [Indenter] using its base
+    /// indentation, as for [Indenter::sub], but naming the scope
+    ///
+    /// The names of the enclosing named scopes form the scope path,
+    /// which is used (for example) in page headers and errors
+    pub fn sub_named<S: Into<Cow<'a, str>>>(&mut self, name: S) -> SubIndenter<'_, 'a, Opt> {
+        let frame = Frame {
+            name: Some(name.into()),
+            ..Default::default()
+        };
+        self.subframe(frame)
+    }
+
+    //fp with_sub
+    /// Invoke a function with a new subframe of the [Indenter], using
+    /// the base indentation, which is popped when the function
+    /// returns
+    ///
+    /// As the [Indenter] is mutably borrowed for the call, the
+    /// function cannot write to it rather than to the subframe, nor
+    /// can it keep the subframe
+    pub fn with_sub<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let mut sub = self.sub();
+        f(&mut sub)
+    }
+
+    //fp with_push
+    /// Invoke a function with a new subframe of the [Indenter], using
+    /// a specific indentation string for this level, which is popped
+    /// when the function returns
+    ///
+    /// As the [Indenter] is mutably borrowed for the call, the
+    /// function cannot write to it rather than to the subframe, nor
+    /// can it keep the subframe
+    pub fn with_push<S, R, F>(&mut self, s: S, f: F) -> R
+    where
+        S: Into<Cow<'a, str>>,
+        F: FnOnce(&mut Self) -> R,
+    {
+        let mut sub = self.push(s);
+        f(&mut sub)
+    }
+
+    //fp set_base_indent
+    /// Set the base indentation string for the subframes of this
+    /// frame of the [Indenter] (and their subframes) that do not
+    /// have their own, until this frame is dropped
+    ///
+    /// The indentation of this frame itself is unchanged; so, for
+    /// example, a section of output may use tabs for its nested
+    /// lines
+    pub fn set_base_indent<S: Into<Cow<'a, str>>>(&self, s: S) {
+        let depth = self.depth;
+        let root = self.root.clone();
+        root.borrow_mut().bases.push((depth, s.into()));
+    }
+
+    //fp push_options
+    /// Create a new subframe of the [Indenter] within which the given
+    /// options override those of the [Indenter]; the options are
+    /// restored when the subframe is dropped
+    ///
+    /// This may be used to (for example) display a section of the
+    /// output more compactly
+    pub fn push_options(&mut self, options: &'a Opt) -> SubIndenter<'_, 'a, Opt> {
+        // The override is pushed first, so that the frame is pushed
+        // (and popped) under the options it is to use
+        let depth = self.depth;
+        self.root
+            .borrow_mut()
+            .option_overrides
+            .push((depth, options));
+        self.sub()
+    }
+
+    //fp with_options
+    /// Invoke a function with a new subframe of the [Indenter] within
+    /// which the given options override those of the [Indenter], as
+    /// for [Indenter::push_options]
+    pub fn with_options<R, F>(&mut self, options: &'a Opt, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let mut sub = self.push_options(options);
+        f(&mut sub)
+    }
+
+    //fp sub_tagged
+    /// Create a new subframe of the [Indenter] whose lines are tagged
+    /// with a severity - each line is prefixed (after its
+    /// indentation) with the severity marker of the options
+    ///
+    /// The tag is counted once, for the severity summary
+    pub fn sub_tagged(&mut self, severity: Severity) -> SubIndenter<'_, 'a, Opt> {
+        let frame = Frame {
+            severity: Some(severity),
+            ..Default::default()
+        };
+        self.subframe(frame)
+    }
+
+    //fp tagged
+    /// Invoke a function with the lines it starts tagged with a
+    /// severity, without creating a new frame (so the indentation is
+    /// not affected)
+    ///
+    /// The tag is counted once, for the severity summary
+    pub fn tagged<R, F>(&mut self, severity: Severity, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        // The tag is not associated with a depth, so it is never
+        // popped with a frame
+        self.root.borrow_mut().push_severity(usize::MAX, severity);
+        let result = f(self);
+        self.root.borrow_mut().severities.pop();
+        result
+    }
+
+    //fp severity_count
+    /// Get the number of tags of a severity so far (excluding those
+    /// whose output was discarded)
+    pub fn severity_count(&self, severity: Severity) -> usize {
+        self.root.borrow().severity_counts[severity.index()]
     }
 
     //fp push
@@ -356,36 +1558,826 @@ impl<'a, Opt: IndentedOptions<'a>> Indenter<'a, Opt> {
     /// from the indentation output stack when it is *dropped*, so it
     /// must either go out of scope or be explicitly dropped.
     ///
-    /// Currently the string must outlive the Indenter - usually this
-    /// means it is static.
-    pub fn push(&self, s: &'a str) -> Self {
-        let node = Inner::subnode(&self.node, Some(s));
-        Self { node }
+    /// The string may be borrowed (in which case it must outlive the
+    /// Indenter - usually this means it is static) or owned, such as
+    /// a `String` computed at runtime.
+    pub fn push<S: Into<Cow<'a, str>>>(&mut self, s: S) -> SubIndenter<'_, 'a, Opt> {
+        let frame = Frame {
+            ind: Some(s.into()),
+            ..Default::default()
+        };
+        self.subframe(frame)
     }
 
-    //dp pop
-    /// Pop this subframe and return its parent
-    pub fn pop(self) -> Self {
-        let node = Inner::take_parent(self.node);
-        Self { node }
+    //fp push_repeat
+    /// Create a new subframe of the [Indenter] as for
+    /// [Indenter::push], using an indentation string of a character
+    /// repeated a number of times
+    pub fn push_repeat(&mut self, c: char, n: usize) -> SubIndenter<'_, 'a, Opt> {
+        self.push(repeat_char(c, n))
+    }
+
+    //fp push_named
+    /// Create a new subframe of the [Indenter] using a specific
+    /// indentation string, as for [Indenter::push], but naming the
+    /// scope
+    pub fn push_named<I, S>(&mut self, s: I, name: S) -> SubIndenter<'_, 'a, Opt>
+    where
+        I: Into<Cow<'a, str>>,
+        S: Into<Cow<'a, str>>,
+    {
+        let frame = Frame {
+            ind: Some(s.into()),
+            name: Some(name.into()),
+            ..Default::default()
+        };
+        self.subframe(frame)
+    }
+
+    //fp current_path
+    /// Get the names of the named scopes enclosing the current
+    /// output, outermost first
+    ///
+    /// This is the same path as is reported in page headers and in
+    /// an [Error]
+    pub fn current_path(&self) -> Vec<String> {
+        self.root.borrow().scope_path()
+    }
+
+    //fp labelled
+    /// Invoke a function with a label added to the scope path, without
+    /// creating a new frame (so the indentation and line are not
+    /// affected)
+    ///
+    /// This is used to add (for example) field names to the scope
+    /// path, so that they may be used by [IndentedOptions::redact]
+    pub fn labelled<S, R, F>(&mut self, label: S, f: F) -> R
+    where
+        S: Into<Cow<'a, str>>,
+        F: FnOnce(&mut Self) -> R,
+    {
+        // Labels are not associated with a depth, so they are never
+        // popped with a frame
+        self.root
+            .borrow_mut()
+            .scopes
+            .push((usize::MAX, label.into()));
+        let result = f(self);
+        self.root.borrow_mut().scopes.pop();
+        result
+    }
+
+    //fp redacted
+    /// Get the text of a value to output, after the redaction hook of
+    /// the options is applied with the current scope path
+    pub fn redacted<'v>(&self, value: &'v str) -> Cow<'v, str> {
+        let options = self.options();
+        match options.redact(&self.current_path(), value) {
+            Some(redacted) => Cow::Owned(redacted.into_owned()),
+            None => Cow::Borrowed(value),
+        }
+    }
+
+    //fp muted
+    /// Create a new subframe of the [Indenter] within which all output
+    /// is discarded; output resumes when the subframe is dropped
+    ///
+    /// This permits verbose sections of output to be switched off
+    /// without every implementation of [crate::IndentedDisplay]
+    /// within them checking a condition
+    pub fn muted(&mut self) -> SubIndenter<'_, 'a, Opt> {
+        let frame = Frame {
+            muted: true,
+            ..Default::default()
+        };
+        self.subframe(frame)
+    }
+
+    //fp if_verbose
+    /// Create a new subframe of the [Indenter] within which all output
+    /// is discarded unless the verbosity of the options is at least
+    /// the given level
+    ///
+    /// Implementations of [crate::IndentedDisplay] may then output
+    /// (for example) children at level 1 and leaf details at level 2,
+    /// for `-v` and `-vv` of a command line tool
+    pub fn if_verbose(&mut self, level: usize) -> SubIndenter<'_, 'a, Opt> {
+        let frame = Frame {
+            muted: self.options().verbosity() < level,
+            ..Default::default()
+        };
+        self.subframe(frame)
+    }
+
+    //fp headered
+    /// Write a header line and create a new subframe of the
+    /// [Indenter] for the output beneath it; the header line is
+    /// completed, so the output of the subframe starts on a new line
+    ///
+    /// Any error writing the header is recorded, and may be retrieved
+    /// with [Indenter::last_error].
+    pub fn headered(&mut self, title: &str) -> SubIndenter<'_, 'a, Opt> {
+        {
+            let mut root = self.root.borrow_mut();
+            let _ = root.write_str(title);
+            root.pending_newline = true;
+        }
+        self.sub()
+    }
+
+    //fp section
+    /// Write a section title, underlined to its width with the
+    /// section underline character of the options, and create a new
+    /// subframe of the [Indenter] for the contents of the section
+    ///
+    /// Any error writing the title is recorded, and may be retrieved
+    /// with [Indenter::last_error].
+    pub fn section(&mut self, title: &str) -> SubIndenter<'_, 'a, Opt> {
+        let c = self.options().section_underline();
+        let underline = repeat_char(c, text::width(title));
+        let _ = writeln!(self.root.borrow_mut(), "{}", title);
+        self.headered(&underline)
+    }
+
+    //fp block
+    /// Write an opening line and create a new subframe of the
+    /// [Indenter] for the contents of a block; when the subframe is
+    /// dropped the closing line is written at the depth of this
+    /// frame
+    ///
+    /// For example, `ind.block("struct Foo {", "}")` for a
+    /// brace-delimited structure. Any error writing the lines is
+    /// recorded, and may be retrieved with [Indenter::last_error].
+    pub fn block<C: Into<Cow<'a, str>>>(
+        &mut self,
+        open: &str,
+        close: C,
+    ) -> SubIndenter<'_, 'a, Opt> {
+        {
+            let mut root = self.root.borrow_mut();
+            let _ = root.write_str(open);
+            root.pending_newline = true;
+        }
+        let frame = Frame {
+            close: Some(close.into()),
+            ..Default::default()
+        };
+        self.subframe(frame)
+    }
+
+    //fp verbatim
+    /// Create a new subframe of the [Indenter] within which all output
+    /// is written raw - without indentation (starting at column 0),
+    /// and without the width limits, padding or severity markers of
+    /// the options; normal indentation resumes when the subframe is
+    /// dropped
+    ///
+    /// This permits pre-formatted blocks (such as code snippets or
+    /// diff hunks) to be embedded in the output
+    pub fn verbatim(&mut self) -> SubIndenter<'_, 'a, Opt> {
+        let frame = Frame {
+            verbatim: true,
+            ..Default::default()
+        };
+        self.subframe(frame)
+    }
+
+    //fp observe_depth
+    /// Register an observer that is invoked on every push and pop of a
+    /// frame of the [Indenter], replacing any previous observer
+    ///
+    /// The observer is given the kind of change, the new depth, and
+    /// the name of the scope being pushed or popped if it is named;
+    /// this permits progress indication, profiling or validation of
+    /// the structure during rendering. The observer must not use the
+    /// indenter itself.
+    pub fn observe_depth<F: FnMut(DepthChange, usize, Option<&str>) + 'a>(&self, observer: F) {
+        self.root.borrow_mut().observer = Some(Box::new(observer));
+    }
+
+    //fp mirror_scopes
+    /// Route the lines output within scopes whose path matches a
+    /// predicate to a writer as well as to the output of the
+    /// [Indenter] (for example, to mirror "errors" scopes to stderr)
+    ///
+    /// The predicate is given the scope path at the start of each
+    /// line; routes are configured once for all subsequent output
+    pub fn mirror_scopes<P, W>(&self, predicate: P, writer: W)
+    where
+        P: Fn(&[String]) -> bool + 'a,
+        W: std::io::Write + 'a,
+    {
+        self.add_route(Box::new(predicate), Box::new(IoSink::new(writer)), false);
+    }
+
+    //fp divert_scopes
+    /// Route the lines output within scopes whose path matches a
+    /// predicate to a writer instead of to the output of the
+    /// [Indenter] (for example, to write "details" scopes only to a
+    /// log file)
+    ///
+    /// The predicate is given the scope path at the start of each
+    /// line; routes are configured once for all subsequent output
+    pub fn divert_scopes<P, W>(&self, predicate: P, writer: W)
+    where
+        P: Fn(&[String]) -> bool + 'a,
+        W: std::io::Write + 'a,
+    {
+        self.add_route(Box::new(predicate), Box::new(IoSink::new(writer)), true);
+    }
+
+    //fp tee
+    /// Send all subsequent output of the [Indenter] to another
+    /// [IndentSink] as well as to its output - such as to stderr as
+    /// well as to a log file
+    ///
+    /// An indenter may be teed to any number of sinks. The output is
+    /// written to all of them even if one fails; the first error is
+    /// returned
+    pub fn tee<T: IndentSink<'a>>(&self, sink: T) {
+        let mut root = self.root.borrow_mut();
+        let first = std::mem::replace(&mut root.fmt, Box::new(IoSink::new(std::io::sink())));
+        let second = sink.into_sink().0;
+        root.fmt = Box::new(Tee { first, second });
+    }
+
+    //fi add_route
+    /// Add a route to the root of the [Indenter]
+    fn add_route(&self, predicate: ScopePredicate<'a>, sink: Box<dyn Sink + 'a>, exclusive: bool) {
+        self.root.borrow_mut().routes.push(Route {
+            predicate,
+            sink,
+            exclusive,
+            active: false,
+        });
+    }
+
+    //fp set_enabled
+    /// Enable or disable all output of the [Indenter], for all of its
+    /// frames, until it is changed again
+    ///
+    /// Output within a [Indenter::muted] frame is discarded even if
+    /// the indenter is enabled
+    pub fn set_enabled(&self, enabled: bool) {
+        self.root.borrow_mut().enabled = enabled;
+    }
+
+    //fp depth
+    /// Get the depth of this frame of the [Indenter] - zero for the
+    /// base [Indenter], one for a subframe of it, and so on
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    //fp is_start_of_line
+    /// Return true if the next output will start a new line - so
+    /// nothing has been written to the current line, or a frame has
+    /// been pushed or popped since it was last written to (which
+    /// starts a new line)
+    ///
+    /// An implementation may use this to avoid writing a redundant
+    /// newline or separator
+    pub fn is_start_of_line(&self) -> bool {
+        let root = self.root.borrow();
+        root.sol || root.pending_newline
+    }
+
+    //fp current_prefix
+    /// Get the indentation string that would be output at the start
+    /// of the next line at the current depth, taking into account
+    /// the indentation strings of pushed frames
+    ///
+    /// An implementation may use this to determine how much of the
+    /// width of the output remains for its content; any severity
+    /// marker that follows the indentation is not included
+    pub fn current_prefix(&self) -> String {
+        self.root.borrow().indent_string()
+    }
+
+    //fp is_muted
+    /// Return true if output is currently being discarded, so that an
+    /// implementation may skip expensive work that would not be shown
+    pub fn is_muted(&self) -> bool {
+        self.root.borrow().is_muted()
+    }
+
+    //fp value
+    /// Display a value within the output of another, such as an
+    /// element of a collection or a field of a struct
+    ///
+    /// The value is displayed by its [crate::IndentedDisplay::summarize]
+    /// method, rather than in full, if it is deeper than the maximum
+    /// depth of the options, or if the options limit the lines of a
+    /// summary and it would output more
+    pub fn value<T: crate::IndentedDisplay<'a, Opt> + ?Sized>(&mut self, x: &T) -> FmtResult {
+        let options = self.options();
+        let (depth, sizing) = {
+            let sizing = self.root.borrow().sizing;
+            (self.depth + sizing.unwrap_or(0), sizing.is_some())
+        };
+        if let Some(max) = options.max_depth().filter(|max| depth > *max) {
+            if !sizing {
+                let root = self.root.clone();
+                root.borrow_mut()
+                    .record_abridgement(IndentError::DepthExceeded { depth, max });
+            }
+            return x.summarize(self);
+        }
+        if let (false, Some(max)) = (sizing, options.summary_lines()) {
+            let lines = match x.estimated_size(options) {
+                Some(estimate) => estimate.lines,
+                None => count_lines(x, options, depth),
+            };
+            if lines > max {
+                return x.summarize(self);
+            }
+        }
+        let result = x.indent(self);
+        if result.is_err() {
+            let root = self.root.clone();
+            let mut root = root.borrow_mut();
+            if root.last_error.is_none() {
+                root.record_error(IndentError::Fmt);
+            }
+        }
+        result
+    }
+
+    //fp merge
+    /// Display a sequence of [crate::RenderedBlock]s, in order
+    ///
+    /// This is used to merge the output of values rendered in parallel
+    /// (for example by worker threads) in a deterministic order, such
+    /// as that in which the work was submitted
+    pub fn merge<I: IntoIterator<Item = crate::RenderedBlock>>(&mut self, blocks: I) -> FmtResult {
+        use crate::IndentedDisplay;
+        for block in blocks {
+            block.indent(self)?;
+        }
+        Ok(())
+    }
+
+    //fp page_break
+    /// Start a new page before any further output
+    ///
+    /// The current line is completed, and the page separator (and
+    /// page header, if any) from the options are output before the
+    /// next line of output
+    pub fn page_break(&self) {
+        self.root.borrow_mut().page_break();
+    }
+
+    //fp marker
+    /// Write a marker (such as an elision or truncation marker) from
+    /// a template, usually supplied by the options
+    ///
+    /// In the template `{n}` is replaced by the count `n` (or removed
+    /// if that is None), and `{depth}` by the current depth of
+    /// indentation
+    pub fn marker(&mut self, template: &str, n: Option<usize>) -> FmtResult {
+        use std::fmt::Write;
+        let depth = self.depth;
+        let n = n.map(|n| n.to_string()).unwrap_or_default();
+        let text = template
+            .replace("{n}", &n)
+            .replace("{depth}", &depth.to_string());
+        self.write_str(&text)
+    }
+
+    //fp write_line
+    /// Write text as a complete line (or lines), adding a newline if
+    /// the text does not end with one
+    pub fn write_line(&mut self, s: &str) -> FmtResult {
+        self.write_str(s)?;
+        if s.ends_with('\n') {
+            Ok(())
+        } else {
+            self.write_str("\n")
+        }
+    }
+
+    //fp ensure_blank_line
+    /// Complete the current line, if it has been started, and write a
+    /// blank line to separate it from what follows - unless the last
+    /// line written was blank, or nothing has yet been written
+    ///
+    /// This permits sections of output to be separated by exactly one
+    /// blank line, however they are composed
+    pub fn ensure_blank_line(&mut self) -> FmtResult {
+        self.root.borrow_mut().ensure_blank_line()
+    }
+
+    //fp lines
+    /// Write each of the items as a line of its own, as for
+    /// [Indenter::write_line]
+    pub fn lines<I>(&mut self, lines: I) -> FmtResult
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for line in lines {
+            self.write_line(line.as_ref())?;
+        }
+        Ok(())
+    }
+
+    //fp last_error
+    /// Take the most recent failure, if any - an error from the
+    /// underlying writer, a formatting error from a value displayed
+    /// with [Indenter::value], or a line that exceeded the maximum
+    /// width with [Overflow::Error]
+    ///
+    /// When a write fails the [std::fmt::Write] implementation can
+    /// only return a [std::fmt::Error]; the cause, and the path of
+    /// named scopes at which it occurred, are retained by the
+    /// indenter and returned (and cleared) by this method
+    pub fn last_error(&self) -> Option<Error> {
+        self.root.borrow_mut().last_error.take()
+    }
+
+    //fp take_io_error
+    /// Take the most recent failure if it was an error from the
+    /// underlying writer, returning that error (such as "disk full")
+    ///
+    /// Any other failure is left to be taken by
+    /// [Indenter::last_error]
+    pub fn take_io_error(&self) -> Option<std::io::Error> {
+        let root = self.root.clone();
+        let mut root = root.borrow_mut();
+        root.last_error.as_ref()?.io_error()?;
+        root.last_error.take().map(Error::into_io_error)
+    }
+
+    //fp flush
+    /// Flush the underlying writer (and the writers of any routes),
+    /// so that the output so far reaches its destination, such as a
+    /// terminal or a pipe consumed by another process
+    ///
+    /// Output that is held back by the indenter - such as a value
+    /// being summarized, a scope being timed in its header line, or
+    /// a block not yet completed for a [SharedTarget] - is not
+    /// written by this; it is written when it would otherwise be
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.root.borrow_mut().sink().flush()
+    }
+
+    //fp finish
+    /// Finish the output of the [Indenter], completing any pending
+    /// line and writing any summaries, and return the first error
+    /// from doing so - or, failing that, the most recent failure not
+    /// yet taken by [Indenter::last_error]
+    ///
+    /// Dropping the base [Indenter] also completes the output, but
+    /// any errors are then lost; so this should be used when the
+    /// writer may fail, such as a file or a socket.
+    pub fn finish(self) -> std::io::Result<()> {
+        let root = self.root.clone();
+        let mut root = root.borrow_mut();
+        root.complete()?;
+        match root.last_error.take() {
+            Some(e) => Err(e.into_io_error()),
+            None => Ok(()),
+        }
+    }
+
+    //fp stats
+    /// Get the statistics of the output written so far - the number
+    /// of bytes and of lines, and the greatest depth of any line
+    ///
+    /// Output that is held back by the indenter (such as a value
+    /// being summarized) is counted when it is written; output
+    /// diverted to the writers of routes is not counted
+    pub fn stats(&self) -> RenderStats {
+        self.root.borrow().stats
+    }
+
+    //fp scope_timings
+    /// Get the scope paths and wall-clock durations of the named
+    /// scopes that have completed, in the order they completed, if
+    /// the options time them (see [IndentedOptions::scope_timing])
+    pub fn scope_timings(&self) -> Vec<(Vec<String>, Duration)> {
+        self.root.borrow().timings.clone()
+    }
+
+    //fp take_abridgements
+    /// Take the conditions in which the output was abridged as the
+    /// options require - lines truncated at the maximum width and
+    /// values summarized at the maximum depth - since they were last
+    /// taken
+    ///
+    /// These are not failures, so they are not reported by
+    /// [Indenter::last_error]
+    pub fn take_abridgements(&self) -> Vec<Error> {
+        std::mem::take(&mut self.root.borrow_mut().abridgements)
     }
 
     //fp options
-    /// Borrow the options used to invoke the [Indenter].
+    /// Borrow the options used to invoke the [Indenter], or those
+    /// that override them for the current frame.
     ///
     /// This may be invoked by the
     /// `indent` function in an [crate::IndentedDisplay] trait implementation
     /// to determine the setting of indentation options that may affect its output.
-    pub fn options(&self) -> &Opt {
-        &self.node.borrow().root.borrow().options
+    pub fn options(&self) -> &'a Opt {
+        self.root.borrow().options()
     }
 
     //zz All done
 }
 
+//a Functions
+//fi count_lines
+/// Count the lines of output of a value displayed at a depth, to
+/// determine if it must be summarized
+///
+/// Values within it are not themselves sized (so the cost is not
+/// compounded at every depth), but the maximum depth still applies
+fn count_lines<'a, Opt, T>(x: &T, options: &'a Opt, depth: usize) -> usize
+where
+    Opt: IndentedOptions,
+    T: crate::IndentedDisplay<'a, Opt> + ?Sized,
+{
+    let recorder = crate::sinks::Recorder::default();
+    {
+        let sink = Box::new(IoSink::new(recorder.clone()));
+        let mut ind = Indenter::with_sink(sink, "", options);
+        ind.root.borrow_mut().sizing = Some(depth);
+        let _ = x.indent(&mut ind);
+    }
+    recorder.take().lines().count()
+}
+
+//fp copy_indented
+/// Copy the lines read from a reader to an [Indenter], each at its
+/// current indentation, returning the number of bytes read
+///
+/// The lines are read one at a time, so (for example) the output of
+/// an external tool or a large file may be embedded in the output
+/// without it being held in memory. Line endings ("\n" or "\r\n")
+/// are replaced by those of the indenter, and a final line without
+/// one is completed; the bytes are converted as for the
+/// [std::io::Write] implementation of the [Indenter].
+pub fn copy_indented<'a, R, Opt>(mut reader: R, ind: &mut Indenter<'a, Opt>) -> std::io::Result<u64>
+where
+    R: std::io::BufRead,
+    Opt: IndentedOptions,
+{
+    let mut line = Vec::new();
+    let mut total = 0;
+    loop {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            return Ok(total);
+        }
+        total += n as u64;
+        copy_line(&line, ind)?;
+    }
+}
+
+//fi copy_line
+/// Copy a line of bytes to an [Indenter], replacing its line ending
+/// (if any) with that of the indenter
+pub(crate) fn copy_line<'a, Opt>(line: &[u8], ind: &mut Indenter<'a, Opt>) -> std::io::Result<()>
+where
+    Opt: IndentedOptions,
+{
+    use std::io::Write;
+    let text = line.strip_suffix(b"\n").unwrap_or(line);
+    let text = text.strip_suffix(b"\r").unwrap_or(text);
+    ind.write_all(text)?;
+    ind.write_all(b"\n")
+}
+
+//fi repeat_char
+/// Create an indentation string of a character repeated a number of
+/// times
+fn repeat_char(c: char, n: usize) -> String {
+    std::iter::repeat_n(c, n).collect()
+}
+
+//a OwnedIndenter
+//tp OwnedIndenter
+/// An [Indenter] that owns its writer, created by
+/// [Indenter::new_owned]
+///
+/// This dereferences to the [Indenter], so it may be used wherever
+/// an [Indenter] is; when the output is complete the writer is
+/// returned by [OwnedIndenter::into_inner]
+pub struct OwnedIndenter<'a, W, Opt: IndentedOptions> {
+    indenter: Indenter<'a, Opt>,
+    writer: OwnedWriter<W>,
+}
+
+//ip OwnedIndenter
+impl<'a, W, Opt: IndentedOptions> OwnedIndenter<'a, W, Opt> {
+    //mp into_inner
+    /// Complete the output of the indenter and return its writer
+    pub fn into_inner(self) -> W {
+        let Self { indenter, writer } = self;
+        drop(indenter);
+        writer
+            .take()
+            .expect("The writer is only taken by into_inner")
+    }
+}
+
+//ip Deref for OwnedIndenter
+impl<'a, W, Opt: IndentedOptions> std::ops::Deref for OwnedIndenter<'a, W, Opt> {
+    type Target = Indenter<'a, Opt>;
+    fn deref(&self) -> &Indenter<'a, Opt> {
+        &self.indenter
+    }
+}
+
+//ip DerefMut for OwnedIndenter
+impl<'a, W, Opt: IndentedOptions> std::ops::DerefMut for OwnedIndenter<'a, W, Opt> {
+    fn deref_mut(&mut self) -> &mut Indenter<'a, Opt> {
+        &mut self.indenter
+    }
+}
+
+//a IndenterDyn
+//tp IndenterDyn
+/// An [Indenter] whose options are a [DynOptions], so that they may be
+/// of any type
+///
+/// An implementation of [crate::IndentedDisplay] for `DynOptions` is
+/// compiled once, rather than once for each type of options; this may
+/// reduce compile times and binary sizes when there are many
+/// implementations and several types of options. It has the same API
+/// as any other [Indenter].
+pub type IndenterDyn<'a> = Indenter<'a, DynOptions<'a>>;
+
+//a SyncIndenter
+//tp SyncIndenter
+/// A thread-safe handle for indented output to a [SharedTarget],
+/// which (provided the options are `Sync`) is `Send` and `Sync`, so
+/// that it may be shared with worker threads or stored in types that
+/// must be `Send`
+///
+/// An [Indenter] itself is confined to a single thread; each thread
+/// obtains one from the handle with [SyncIndenter::indenter] (or
+/// [SyncIndenter::with]), with the full API of the [Indenter]. As
+/// for [Indenter::shared], each top-level block of output is written
+/// atomically, so the output of threads is not interleaved.
+#[derive(Debug)]
+pub struct SyncIndenter<'a, Opt: IndentedOptions> {
+    /// The target that the indenters write to
+    target: SharedTarget,
+    /// The base indentation string of the indenters
+    ind: Cow<'a, str>,
+    /// The options of the indenters
+    options: &'a Opt,
+}
+
+//ip SyncIndenter
+impl<'a, Opt: IndentedOptions> SyncIndenter<'a, Opt> {
+    //fp new
+    /// Create a new [SyncIndenter] writing to a new [SharedTarget]
+    /// for a writer
+    pub fn new<W, S>(writer: W, s: S, options: &'a Opt) -> Self
+    where
+        W: std::io::Write + Send + 'static,
+        S: Into<Cow<'a, str>>,
+    {
+        Self::for_target(&SharedTarget::new(writer), s, options)
+    }
+
+    //fp for_target
+    /// Create a new [SyncIndenter] writing to a [SharedTarget], which
+    /// may also be written to by other indenters
+    pub fn for_target<S: Into<Cow<'a, str>>>(
+        target: &SharedTarget,
+        s: S,
+        options: &'a Opt,
+    ) -> Self {
+        Self {
+            target: target.clone(),
+            ind: s.into(),
+            options,
+        }
+    }
+
+    //ap target
+    /// Get the [SharedTarget] that the indenters write to
+    pub fn target(&self) -> &SharedTarget {
+        &self.target
+    }
+
+    //mp indenter
+    /// Create an [Indenter] for the current thread that writes to the
+    /// target
+    pub fn indenter(&self) -> Indenter<'a, Opt> {
+        Indenter::shared(&self.target, self.ind.clone(), self.options)
+    }
+
+    //mp with
+    /// Invoke a function with an [Indenter] for the current thread,
+    /// completing its output when the function returns
+    ///
+    /// Any error completing the output is lost; use
+    /// [SyncIndenter::indenter] and [Indenter::finish] if it is
+    /// required.
+    pub fn with<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Indenter<'a, Opt>) -> R,
+    {
+        f(&mut self.indenter())
+    }
+}
+
+//ip Clone for SyncIndenter
+impl<'a, Opt: IndentedOptions> Clone for SyncIndenter<'a, Opt> {
+    fn clone(&self) -> Self {
+        Self {
+            target: self.target.clone(),
+            ind: self.ind.clone(),
+            options: self.options,
+        }
+    }
+}
+
+//a SubIndenter
+//tp SubIndenter
+/// A subframe of an [Indenter], created by (for example)
+/// [Indenter::sub] or [Indenter::push], which mutably borrows its
+/// parent frame for its lifetime
+///
+/// This dereferences to the [Indenter] for the subframe, so it may
+/// be used wherever an [Indenter] is; the subframe is popped when it
+/// is dropped, or by [SubIndenter::pop]
+pub struct SubIndenter<'p, 'a, Opt: IndentedOptions> {
+    indenter: Indenter<'a, Opt>,
+    parent: &'p mut Indenter<'a, Opt>,
+}
+
+//ip SubIndenter
+impl<'p, 'a, Opt: IndentedOptions> SubIndenter<'p, 'a, Opt> {
+    //mp pop
+    /// Pop this subframe and return its parent, which may then be
+    /// written to again
+    pub fn pop(self) -> &'p mut Indenter<'a, Opt> {
+        let Self { indenter, parent } = self;
+        drop(indenter);
+        parent
+    }
+}
+
+//ip Deref for SubIndenter
+impl<'a, Opt: IndentedOptions> std::ops::Deref for SubIndenter<'_, 'a, Opt> {
+    type Target = Indenter<'a, Opt>;
+    fn deref(&self) -> &Indenter<'a, Opt> {
+        &self.indenter
+    }
+}
+
+//ip DerefMut for SubIndenter
+impl<'a, Opt: IndentedOptions> std::ops::DerefMut for SubIndenter<'_, 'a, Opt> {
+    fn deref_mut(&mut self) -> &mut Indenter<'a, Opt> {
+        &mut self.indenter
+    }
+}
+
+//ip Write for SubIndenter
+impl<'a, Opt: IndentedOptions> std::fmt::Write for SubIndenter<'_, 'a, Opt> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.indenter.write_str(s)
+    }
+}
+
+//ip io::Write for SubIndenter
+impl<'a, Opt: IndentedOptions> std::io::Write for SubIndenter<'_, 'a, Opt> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        std::io::Write::write(&mut self.indenter, buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::Write::flush(&mut self.indenter)
+    }
+}
+
 //ip Write
-impl<'a, Opt: IndentedOptions<'a>> std::fmt::Write for Indenter<'a, Opt> {
+impl<'a, Opt: IndentedOptions> std::fmt::Write for Indenter<'a, Opt> {
     fn write_str(&mut self, s: &str) -> FmtResult {
-        self.node.borrow().root.borrow_mut().write_str(s)
+        self.root.borrow_mut().write_str(s)
+    }
+}
+
+//ip io::Write
+/// The bytes written are UTF-8 text; a sequence split between writes
+/// is reassembled, and an invalid sequence is replaced with U+FFFD.
+/// An error is returned with the same kind as the underlying error
+impl<'a, Opt: IndentedOptions> std::io::Write for Indenter<'a, Opt> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let result = self.root.borrow_mut().write_bytes(buf);
+        match result {
+            Ok(()) => Ok(buf.len()),
+            Err(e) => Err(self
+                .last_error()
+                .map_or_else(|| std::io::Error::other(e), Error::into_io_error)),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Indenter::flush(self)
     }
 }
@@ -29,36 +29,125 @@ use indent_display::{Indenter, NullOptions, DefaultIndentedDisplay, IndentedDisp
 let mut stdout = std::io::stdout();
 let mut ind = Indenter::new(&mut stdout, "  ", &NullOptions {});
 "Not indented\n".indent(&mut ind);
-{
-    let mut sub = ind.sub();
-    "Indented once with two spaces\n".indent(&mut ind);
-}
-{
-    let mut sub = ind.push("...");
-    "Indented once with three dots\n".indent(&mut ind);
-    {
-        let mut sub = sub.push("***");
-        "Indented with three dots and three stars\nAnd so is this".indent(&mut ind);
-    }
-    {
-        let mut sub = sub.sub();
-        "Indented with three dots and two spaces stars\nAnd so is this".indent(&mut ind);
-    }
-}
+ind.with_sub(|sub| "Indented once with two spaces\n".indent(sub));
+ind.with_push("...", |sub| {
+    "Indented once with three dots\n".indent(sub)?;
+    sub.with_push("***", |sub| {
+        "Indented with three dots and three stars\nAnd so is this\n".indent(sub)
+    })?;
+    sub.with_sub(|sub| {
+        "Indented with three dots and two spaces\nAnd so is this\n".indent(sub)
+    })
+});
 "Not indented\n".indent(&mut ind);
 ```
 
+Subframes may also be created with [Indenter::sub] and
+[Indenter::push], and are popped when they are dropped; the closure
+forms above ensure that the output is written to the subframe, and
+that the subframe is popped.
+
+The output is completed when the base [Indenter] is dropped, but any
+error in doing so is then lost; [Indenter::finish] completes the
+output and returns any error.
+
+With the `icu` feature, numbers may be formatted for a locale (with
+its decimal separator and digit grouping) by providing a decimal
+formatter in the options.
+
+With the `futures-io` feature, `AsyncIndenter` writes to an
+`AsyncWrite`, sending the output as it is produced rather than
+rendering it in full first.
+
+With the `process` feature, `ChildOutput` streams the stdout and
+stderr of a child process through an indenter, labelling each line
+with its stream.
+
+With the `derive` feature, `#[derive(IndentedDisplay)]` is available
+for structs; see the `indent-display-derive` crate for the attributes
+it supports.
+
 !*/
 
 //a Imports
+// Allow the derive macro output, which refers to ::indent_display,
+// to be used within this crate
+extern crate self as indent_display;
+
+mod adapters;
 mod defaults;
+mod error;
+mod filter;
+#[cfg(all(feature = "android_log-sys", target_os = "android"))]
+mod impl_android;
+#[cfg(feature = "arrayvec")]
+mod impl_arrayvec;
+#[cfg(feature = "bitflags")]
+mod impl_bitflags;
+#[cfg(feature = "either")]
+mod impl_either;
+#[cfg(feature = "futures-io")]
+mod impl_futures_io;
+#[cfg(feature = "indexmap")]
+mod impl_indexmap;
+#[cfg(feature = "nalgebra")]
+mod impl_nalgebra;
+#[cfg(feature = "ndarray")]
+mod impl_ndarray;
+#[cfg(feature = "petgraph")]
+mod impl_petgraph;
+#[cfg(feature = "process")]
+mod impl_process;
+#[cfg(feature = "ratatui")]
+mod impl_ratatui;
+#[cfg(feature = "rayon")]
+mod impl_rayon;
+#[cfg(feature = "smallvec")]
+mod impl_smallvec;
 mod indenter;
+mod macros;
+mod sinks;
+#[allow(clippy::module_inception, clippy::useless_vec, clippy::useless_format)]
 mod test;
+mod text;
 mod traits;
 mod types;
 
 //a Exports
-pub use traits::{DefaultIndentedDisplay, IndentedDisplay, IndentedOptions};
-pub use types::NullOptions;
+pub use adapters::{AsDisplay, Bytes, Elided, Matrix, RenderedBlock};
+pub use error::{Error, IndentError, Result};
+pub use filter::{ScopeFilter, ScopeFilterError};
+#[cfg(all(feature = "android_log-sys", target_os = "android"))]
+pub use impl_android::{Logcat, LogcatPriority};
+#[cfg(feature = "bitflags")]
+pub use impl_bitflags::FlagsDisplay;
+#[cfg(feature = "futures-io")]
+pub use impl_futures_io::{AsyncIndentedDisplay, AsyncIndenter};
+#[cfg(feature = "petgraph")]
+pub use impl_petgraph::GraphDisplay;
+#[cfg(feature = "process")]
+pub use impl_process::ChildOutput;
+#[cfg(feature = "ratatui")]
+pub use impl_ratatui::RatatuiText;
+#[cfg(feature = "rayon")]
+pub use impl_rayon::ParSlice;
+#[cfg(feature = "derive")]
+pub use indent_display_derive::IndentedDisplay;
+pub use traits::{DefaultIndentedDisplay, DynOptions, IndentedDisplay, IndentedOptions};
+pub use types::{
+    BasicOptions, BytesFormat, DeepFrames, DepthChange, FinalNewline, FlushPolicy, IndentStyle,
+    IndentedLines, Metrics, NullOptions, Overflow, RenderStats, ScopeTiming, Severity,
+    SizeEstimate,
+};
 // pub use defaults::{};
-pub use indenter::Indenter;
+/// The ICU crates used for locale-aware formatting (with the `icu`
+/// feature), so that a formatter may be created for the options
+#[cfg(feature = "icu")]
+pub mod icu {
+    pub use icu_decimal as decimal;
+    pub use icu_locale_core as locale;
+}
+pub use indenter::{
+    copy_indented, Indenter, IndenterDyn, OwnedIndenter, SubIndenter, SyncIndenter,
+};
+pub use sinks::{IndentSink, IoTarget, SharedTarget};
@@ -0,0 +1,49 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    macros.rs
+@brief   Macros exported by the crate
+ */
+
+//a Macros
+//mp impl_default_indented
+/// Mark one or more types as [crate::DefaultIndentedDisplay], so that
+/// they are displayed through an [crate::Indenter] using their
+/// [std::fmt::Display] implementation
+///
+/// Each type must implement [std::fmt::Display]; generic types must
+/// be given with concrete parameters.
+///
+/// ```
+/// use indent_display::impl_default_indented;
+/// struct Celsius(f32);
+/// impl std::fmt::Display for Celsius {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "{}C", self.0)
+///     }
+/// }
+/// struct Id<T>(T);
+/// impl<T: std::fmt::Display> std::fmt::Display for Id<T> {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "#{}", self.0)
+///     }
+/// }
+/// impl_default_indented!(Celsius, Id<u32>, Id<String>);
+/// ```
+#[macro_export]
+macro_rules! impl_default_indented {
+    ($($t:ty),* $(,)?) => {
+        $(impl $crate::DefaultIndentedDisplay for $t {})*
+    };
+}
@@ -0,0 +1,849 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    sinks.rs
+@brief   Output sinks used internally by the indenter
+ */
+
+//a Imports
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::RenderStats;
+
+//a Sink
+//ti Sink
+/// The output of an indenter, as a sequence of events rather than a
+/// stream of bytes
+///
+/// Every line of output starts with an indent event (which gives the
+/// depth and the indentation string), followed by text events, and
+/// is ended by a newline event with its line ending (the final line
+/// may not be ended)
+pub(crate) trait Sink {
+    /// Start a line at the given depth with its indentation string
+    fn write_indent(&mut self, depth: usize, indent: &str) -> std::io::Result<()>;
+    /// Write text (which contains no newlines) to the current line
+    fn write_text(&mut self, text: &str) -> std::io::Result<()>;
+    /// End the current line with the given line ending
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()>;
+    /// Write a page break, with the page header line (including its
+    /// line ending) if any
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()>;
+    /// Invoked when the output up to this point is a complete
+    /// top-level block - when the first line at the base depth after
+    /// a top-level frame was popped has been completed, and when the
+    /// output is complete
+    fn end_block(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+    /// Flush any output buffered by the underlying writer
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+//a IndentSink
+//mi private
+/// The sealing of [IndentSink], which also provides the conversion
+/// to a [Sink]
+pub(crate) mod private {
+    /// A boxed [super::Sink], which may not be used outside of this
+    /// crate
+    pub struct BoxedSink<'a>(pub(crate) Box<dyn super::Sink + 'a>);
+
+    pub trait Sealed<'a> {
+        /// Convert the target into a boxed [super::Sink]
+        fn into_sink(self) -> BoxedSink<'a>;
+    }
+}
+
+//tp IndentSink
+/// A target for the output of an [crate::Indenter], created with
+/// [crate::Indenter::for_sink]
+///
+/// This is implemented for mutable references to [std::io::Write]
+/// and [std::fmt::Write] trait objects, `String`, `Vec<u8>`,
+/// [std::fs::File], [std::io::BufWriter], [std::io::LineWriter],
+/// [std::io::Cursor], [std::net::TcpStream] and
+/// [std::fmt::Formatter]; for [std::io::Stdout] and [std::io::Stderr]
+/// (which are locked for the lifetime of the indenter); for a
+/// [SharedTarget]; and for any [std::io::Write] wrapped in an
+/// [IoTarget]. It is sealed, and cannot be implemented outside of
+/// this crate.
+pub trait IndentSink<'a>: private::Sealed<'a> {}
+
+impl<'a, T: private::Sealed<'a>> IndentSink<'a> for T {}
+
+//ii io and fmt targets for IndentSink
+impl<'a> private::Sealed<'a> for &'a mut (dyn std::io::Write + 'a) {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self)))
+    }
+}
+impl<'a> private::Sealed<'a> for &'a mut (dyn std::fmt::Write + 'a) {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(FmtSink(self)))
+    }
+}
+impl<'a> private::Sealed<'a> for &'a mut Vec<u8> {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self)))
+    }
+}
+impl<'a> private::Sealed<'a> for &'a mut std::fs::File {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self)))
+    }
+}
+impl<'a> private::Sealed<'a> for &'a mut std::net::TcpStream {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self)))
+    }
+}
+impl<'a, W: std::io::Write + 'a> private::Sealed<'a> for &'a mut std::io::BufWriter<W> {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self)))
+    }
+}
+impl<'a, W: std::io::Write + 'a> private::Sealed<'a> for &'a mut std::io::LineWriter<W> {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self)))
+    }
+}
+impl<'a, T: 'a> private::Sealed<'a> for &'a mut std::io::Cursor<T>
+where
+    std::io::Cursor<T>: std::io::Write,
+{
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self)))
+    }
+}
+impl<'a, W: std::io::Write + 'a> private::Sealed<'a> for IoTarget<W> {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self.0)))
+    }
+}
+impl<'a> private::Sealed<'a> for &'a mut String {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(FmtSink(self)))
+    }
+}
+impl<'a, 'f: 'a> private::Sealed<'a> for &'a mut std::fmt::Formatter<'f> {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(FmtSink(self)))
+    }
+}
+impl<'a> private::Sealed<'a> for std::io::Stdout {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self.lock())))
+    }
+}
+impl<'a> private::Sealed<'a> for std::io::Stderr {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(IoSink::new(self.lock())))
+    }
+}
+impl<'a> private::Sealed<'a> for &SharedTarget {
+    fn into_sink(self) -> private::BoxedSink<'a> {
+        private::BoxedSink(Box::new(self.sink()))
+    }
+}
+
+//a IoTarget
+//tp IoTarget
+/// An [IndentSink] for any [std::io::Write], owned or borrowed, for
+/// writers that it is not directly implemented for - such as
+/// `IoTarget(&mut socket)` or `IoTarget(BufWriter::new(file))`
+///
+/// An owned writer is dropped with the indenter.
+#[derive(Debug)]
+pub struct IoTarget<W: std::io::Write>(pub W);
+
+//a IoSink
+//ti IoSink
+/// A [Sink] that writes the output as text to an [std::io::Write]
+///
+/// Each line (its indentation and all of its text) is assembled in a
+/// buffer and written with a single write, when it is ended; a line
+/// that is not ended is written when the sink is flushed, at the end
+/// of a block, or when the sink is dropped
+pub(crate) struct IoSink<W: std::io::Write> {
+    /// The writer
+    writer: W,
+    /// The line being assembled
+    line: Vec<u8>,
+}
+
+//ii IoSink
+impl<W: std::io::Write> IoSink<W> {
+    //fi new
+    /// Create a new [IoSink] for a writer
+    pub(crate) fn new(writer: W) -> Self {
+        Self {
+            writer,
+            line: Vec::new(),
+        }
+    }
+
+    //fi write_line
+    /// Write the line assembled so far, if any
+    fn write_line(&mut self) -> std::io::Result<()> {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        let result = self.writer.write_all(&self.line);
+        self.line.clear();
+        result
+    }
+}
+
+//ii Sink for IoSink
+impl<W: std::io::Write> Sink for IoSink<W> {
+    fn write_indent(&mut self, _depth: usize, indent: &str) -> std::io::Result<()> {
+        self.line.extend_from_slice(indent.as_bytes());
+        Ok(())
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.line.extend_from_slice(text.as_bytes());
+        Ok(())
+    }
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()> {
+        self.line.extend_from_slice(ending.as_bytes());
+        self.write_line()
+    }
+    fn end_block(&mut self) -> std::io::Result<()> {
+        self.write_line()
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_line()?;
+        self.writer.flush()
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        self.line.extend_from_slice(separator.as_bytes());
+        if let Some(header) = header {
+            self.line.extend_from_slice(header.as_bytes());
+        }
+        self.write_line()
+    }
+}
+
+//ii Drop for IoSink
+impl<W: std::io::Write> Drop for IoSink<W> {
+    fn drop(&mut self) {
+        let _ = self.write_line();
+    }
+}
+
+//a FmtSink
+//ti FmtSink
+/// A [Sink] that writes the output as text to a [std::fmt::Write],
+/// such as a `String` or a [std::fmt::Formatter]
+pub(crate) struct FmtSink<W: std::fmt::Write>(pub W);
+
+//ii FmtSink
+impl<W: std::fmt::Write> FmtSink<W> {
+    //fi write
+    /// Write a string to the formatter, converting a formatting error
+    /// to an I/O error
+    fn write(&mut self, s: &str) -> std::io::Result<()> {
+        self.0.write_str(s).map_err(std::io::Error::other)
+    }
+}
+
+//ii Sink for FmtSink
+impl<W: std::fmt::Write> Sink for FmtSink<W> {
+    fn write_indent(&mut self, _depth: usize, indent: &str) -> std::io::Result<()> {
+        self.write(indent)
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.write(text)
+    }
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()> {
+        self.write(ending)
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        self.write(separator)?;
+        if let Some(header) = header {
+            self.write(header)?;
+        }
+        Ok(())
+    }
+}
+
+//a LineSink
+//ti LineSink
+/// A [Sink] that delivers each completed line of the output, with
+/// its indentation but without its line ending, to a callback
+///
+/// The lines of a page break (its separator and header) are
+/// delivered in the same way; a final line that is not ended is
+/// delivered when the output is complete.
+pub(crate) struct LineSink<F: FnMut(&str)> {
+    /// The line being built
+    line: String,
+    /// The callback the lines are delivered to
+    callback: F,
+}
+
+//ii LineSink
+impl<F: FnMut(&str)> LineSink<F> {
+    //fi new
+    /// Create a new [LineSink] for a callback
+    pub(crate) fn new(callback: F) -> Self {
+        Self {
+            line: String::new(),
+            callback,
+        }
+    }
+}
+
+//ii Sink for LineSink
+impl<F: FnMut(&str)> Sink for LineSink<F> {
+    fn write_indent(&mut self, _depth: usize, indent: &str) -> std::io::Result<()> {
+        self.line.push_str(indent);
+        Ok(())
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.line.push_str(text);
+        Ok(())
+    }
+    fn write_newline(&mut self, _ending: &str) -> std::io::Result<()> {
+        (self.callback)(&self.line);
+        self.line.clear();
+        Ok(())
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        for line in separator
+            .lines()
+            .chain(header.into_iter().flat_map(str::lines))
+        {
+            (self.callback)(line);
+        }
+        Ok(())
+    }
+    fn end_block(&mut self) -> std::io::Result<()> {
+        if !self.line.is_empty() {
+            (self.callback)(&self.line);
+            self.line.clear();
+        }
+        Ok(())
+    }
+}
+
+//a Recorder
+//ti Recorder
+/// A sink that records all of the output of an indenter into a
+/// buffer that is shared with the creator of the sink, so that the
+/// output can be retrieved after the indenter has been dropped
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Recorder {
+    /// The buffer of recorded output
+    buffer: Rc<RefCell<Vec<u8>>>,
+}
+
+//ii Recorder
+impl Recorder {
+    //fi take
+    /// Take the recorded output as a string, leaving the buffer empty
+    ///
+    /// The indenter only writes UTF-8, so the conversion is lossless
+    pub(crate) fn take(&self) -> String {
+        let buffer = std::mem::take(&mut *self.buffer.borrow_mut());
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+//ii io::Write for Recorder
+impl std::io::Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+//a OwnedWriter
+//ti OwnedWriter
+/// An [std::io::Write] that holds a writer owned by an indenter, and
+/// that is shared with the creator of the indenter so that the
+/// writer may be taken back when the indenter is finished with
+///
+/// Once the writer has been taken any further writes fail
+#[derive(Debug)]
+pub(crate) struct OwnedWriter<W> {
+    /// The writer, until it is taken
+    writer: Rc<RefCell<Option<W>>>,
+}
+
+//ii OwnedWriter
+impl<W> OwnedWriter<W> {
+    //fi new
+    /// Create a new [OwnedWriter] holding a writer
+    pub(crate) fn new(writer: W) -> Self {
+        let writer = Rc::new(RefCell::new(Some(writer)));
+        Self { writer }
+    }
+
+    //fi take
+    /// Take the writer, if it has not already been taken
+    pub(crate) fn take(&self) -> Option<W> {
+        self.writer.borrow_mut().take()
+    }
+
+    //fi with
+    /// Invoke a function with the writer, if it has not been taken
+    #[cfg(feature = "futures-io")]
+    pub(crate) fn with<R, F: FnOnce(&mut W) -> R>(&self, f: F) -> Option<R> {
+        self.writer.borrow_mut().as_mut().map(f)
+    }
+}
+
+//ii Clone for OwnedWriter
+impl<W> Clone for OwnedWriter<W> {
+    fn clone(&self) -> Self {
+        let writer = self.writer.clone();
+        Self { writer }
+    }
+}
+
+//ii io::Write for OwnedWriter
+impl<W: std::io::Write> std::io::Write for OwnedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.writer.borrow_mut().as_mut() {
+            Some(w) => w.write(buf),
+            None => Err(std::io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.writer.borrow_mut().as_mut() {
+            Some(w) => w.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+//a DepthRecorder
+//ti DepthRecorder
+/// A [Sink] that records each line of output with its depth and its
+/// indentation, separately from its text, into a buffer that is
+/// shared with the creator of the sink
+///
+/// Page breaks are not recorded
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DepthRecorder {
+    /// The lines recorded so far, as depth, indentation and text
+    lines: Rc<RefCell<Vec<(usize, String, String)>>>,
+}
+
+//ii DepthRecorder
+impl DepthRecorder {
+    //fi take
+    /// Take the recorded lines, leaving the buffer empty
+    pub(crate) fn take(&self) -> Vec<(usize, String, String)> {
+        std::mem::take(&mut *self.lines.borrow_mut())
+    }
+}
+
+//ii Sink for DepthRecorder
+impl Sink for DepthRecorder {
+    fn write_indent(&mut self, depth: usize, indent: &str) -> std::io::Result<()> {
+        self.lines
+            .borrow_mut()
+            .push((depth, indent.to_string(), String::new()));
+        Ok(())
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        if let Some((_, _, line)) = self.lines.borrow_mut().last_mut() {
+            line.push_str(text);
+        }
+        Ok(())
+    }
+    fn write_newline(&mut self, _ending: &str) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn write_page_break(&mut self, _separator: &str, _header: Option<&str>) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+//a Buffer
+//ti Event
+/// An event of a [Sink], as recorded by a [Buffer]
+#[derive(Debug)]
+enum Event {
+    /// Start a line at a depth with its indentation string
+    Indent(usize, String),
+    /// Text within a line
+    Text(String),
+    /// End of a line, with its line ending
+    Newline(String),
+    /// A page break with its separator and page header
+    PageBreak(String, Option<String>),
+}
+
+//ti Buffer
+/// A [Sink] that records the events of its output, so that they may
+/// be replayed to another sink or discarded
+#[derive(Debug, Default)]
+pub(crate) struct Buffer {
+    /// The events recorded
+    events: Vec<Event>,
+    /// The number of lines started
+    lines: usize,
+}
+
+//ii Buffer
+impl Buffer {
+    //fi lines
+    /// The number of lines that have been started in the buffer
+    pub(crate) fn lines(&self) -> usize {
+        self.lines
+    }
+
+    //fi replay
+    /// Replay the recorded events to another sink
+    pub(crate) fn replay(self, sink: &mut dyn Sink) -> std::io::Result<()> {
+        for event in self.events {
+            match event {
+                Event::Indent(depth, indent) => sink.write_indent(depth, &indent)?,
+                Event::Text(text) => sink.write_text(&text)?,
+                Event::Newline(ending) => sink.write_newline(&ending)?,
+                Event::PageBreak(separator, header) => {
+                    sink.write_page_break(&separator, header.as_deref())?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//ii Sink for Buffer
+impl Sink for Buffer {
+    fn write_indent(&mut self, depth: usize, indent: &str) -> std::io::Result<()> {
+        self.lines += 1;
+        self.events.push(Event::Indent(depth, indent.to_string()));
+        Ok(())
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.events.push(Event::Text(text.to_string()));
+        Ok(())
+    }
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()> {
+        self.events.push(Event::Newline(ending.to_string()));
+        Ok(())
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        self.events.push(Event::PageBreak(
+            separator.to_string(),
+            header.map(String::from),
+        ));
+        Ok(())
+    }
+}
+
+//a Routes
+//ti ScopePredicate
+/// A predicate on the scope path at which a line is output
+pub(crate) type ScopePredicate<'a> = Box<dyn Fn(&[String]) -> bool + 'a>;
+
+//ti Route
+/// A route of the output of an indenter to an additional sink, for the
+/// lines output within scopes that match a predicate
+pub(crate) struct Route<'a> {
+    /// The predicate on the scope path at the start of a line
+    pub predicate: ScopePredicate<'a>,
+    /// The sink that matching lines are written to
+    pub sink: Box<dyn Sink + 'a>,
+    /// Set if matching lines are written only to this route, and not
+    /// to the output of the indenter
+    pub exclusive: bool,
+    /// Set if the current line matches the predicate
+    pub active: bool,
+}
+
+//ii Route
+impl<'a> Route<'a> {
+    //fi set_path
+    /// Set the scope path of the current line for all of the routes
+    pub(crate) fn set_path(routes: &mut [Self], path: &[String]) {
+        for route in routes {
+            route.active = (route.predicate)(path);
+        }
+    }
+}
+
+//ti HeldNewline
+/// A [Sink] that forwards to another, counting the output, but that
+/// may hold back each newline until the next event - so that the
+/// final newline of the output may be discarded
+pub(crate) struct HeldNewline<'r, 'a> {
+    /// The sink the events are forwarded to
+    pub sink: &'r mut (dyn Sink + 'a),
+    /// The statistics of the output forwarded
+    pub stats: &'r mut RenderStats,
+    /// The line ending held back, if any
+    pub held: &'r mut Option<String>,
+    /// Set if newlines are to be held back
+    pub hold: bool,
+}
+
+//ii HeldNewline
+impl HeldNewline<'_, '_> {
+    //fi release
+    /// Forward the held newline, if any
+    fn release(&mut self) -> std::io::Result<()> {
+        match self.held.take() {
+            Some(ending) => {
+                self.stats.bytes += ending.len();
+                self.sink.write_newline(&ending)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+//ii Sink for HeldNewline
+impl Sink for HeldNewline<'_, '_> {
+    fn write_indent(&mut self, depth: usize, indent: &str) -> std::io::Result<()> {
+        self.release()?;
+        self.stats.bytes += indent.len();
+        self.stats.lines += 1;
+        self.stats.depth = self.stats.depth.max(depth);
+        self.sink.write_indent(depth, indent)
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.release()?;
+        self.stats.bytes += text.len();
+        self.sink.write_text(text)
+    }
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()> {
+        self.release()?;
+        if self.hold {
+            *self.held = Some(ending.to_string());
+            Ok(())
+        } else {
+            self.stats.bytes += ending.len();
+            self.sink.write_newline(ending)
+        }
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        self.release()?;
+        self.stats.bytes += separator.len() + header.map_or(0, str::len);
+        self.sink.write_page_break(separator, header)
+    }
+    fn end_block(&mut self) -> std::io::Result<()> {
+        self.release()?;
+        self.sink.end_block()
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.sink.flush()
+    }
+}
+
+//ti Tee
+/// A [Sink] that writes every event to two sinks - the output of an
+/// indenter, and a sink that it has been teed to
+///
+/// Each event is written to both sinks even if the first fails; the
+/// first error is returned
+pub(crate) struct Tee<'a> {
+    /// The sink the indenter wrote to before it was teed
+    pub first: Box<dyn Sink + 'a>,
+    /// The sink the output is teed to
+    pub second: Box<dyn Sink + 'a>,
+}
+
+//ii Sink for Tee
+impl Sink for Tee<'_> {
+    fn write_indent(&mut self, depth: usize, indent: &str) -> std::io::Result<()> {
+        let result = self.first.write_indent(depth, indent);
+        result.and(self.second.write_indent(depth, indent))
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        let result = self.first.write_text(text);
+        result.and(self.second.write_text(text))
+    }
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()> {
+        let result = self.first.write_newline(ending);
+        result.and(self.second.write_newline(ending))
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        let result = self.first.write_page_break(separator, header);
+        result.and(self.second.write_page_break(separator, header))
+    }
+    fn end_block(&mut self) -> std::io::Result<()> {
+        let result = self.first.end_block();
+        result.and(self.second.end_block())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        let result = self.first.flush();
+        result.and(self.second.flush())
+    }
+}
+
+//ti Routed
+/// A [Sink] that writes to the buffer of an indenter if it has one,
+/// otherwise to its output and to the sinks of its routes that are
+/// active for the current line
+pub(crate) struct Routed<'r, 'a> {
+    /// The buffer of the innermost scope that may be summarized, if any
+    pub buffer: Option<&'r mut Buffer>,
+    /// The output of the indenter
+    pub fmt: HeldNewline<'r, 'a>,
+    /// The routes of the indenter
+    pub routes: &'r mut [Route<'a>],
+}
+
+//ii Routed
+impl Routed<'_, '_> {
+    //fi each
+    /// Invoke an event on each sink that the current line is routed to
+    fn each<F: FnMut(&mut dyn Sink) -> std::io::Result<()>>(
+        &mut self,
+        mut event: F,
+    ) -> std::io::Result<()> {
+        if let Some(buffer) = &mut self.buffer {
+            return event(*buffer);
+        }
+        let mut exclusive = false;
+        for route in self.routes.iter_mut().filter(|r| r.active) {
+            exclusive |= route.exclusive;
+            event(route.sink.as_mut())?;
+        }
+        if exclusive {
+            Ok(())
+        } else {
+            event(&mut self.fmt)
+        }
+    }
+}
+
+//ii Sink for Routed
+impl Sink for Routed<'_, '_> {
+    fn write_indent(&mut self, depth: usize, indent: &str) -> std::io::Result<()> {
+        self.each(|s| s.write_indent(depth, indent))
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.each(|s| s.write_text(text))
+    }
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()> {
+        self.each(|s| s.write_newline(ending))
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        match &mut self.buffer {
+            Some(buffer) => buffer.write_page_break(separator, header),
+            None => self.fmt.write_page_break(separator, header),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        for route in self.routes.iter_mut() {
+            route.sink.flush()?;
+        }
+        self.fmt.flush()
+    }
+}
+
+//a SharedTarget
+//tp SharedTarget
+/// A writer that may be shared by [crate::Indenter]s on many threads,
+/// created with [crate::Indenter::shared]
+///
+/// Each indenter buffers its output, and writes each complete
+/// top-level block to the writer while holding a lock, so that the
+/// blocks of concurrent indenters are not interleaved. The target may
+/// be cloned cheaply to pass it to other threads.
+#[derive(Clone)]
+pub struct SharedTarget {
+    /// The writer, and the lock that serializes the blocks
+    writer: Arc<Mutex<Box<dyn std::io::Write + Send>>>,
+}
+
+//ip SharedTarget
+impl SharedTarget {
+    //fp new
+    /// Create a new [SharedTarget] for a writer
+    pub fn new<W: std::io::Write + Send + 'static>(writer: W) -> Self {
+        let writer: Box<dyn std::io::Write + Send> = Box::new(writer);
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    //fi sink
+    /// Create a [Sink] for an indenter that writes to this target
+    pub(crate) fn sink(&self) -> SharedSink {
+        SharedSink {
+            target: self.clone(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+//ip Debug for SharedTarget
+impl std::fmt::Debug for SharedTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "SharedTarget")
+    }
+}
+
+//ti SharedSink
+/// The [Sink] of an indenter writing to a [SharedTarget], which
+/// buffers each top-level block
+pub(crate) struct SharedSink {
+    /// The target to write the blocks to
+    target: SharedTarget,
+    /// The current block
+    buffer: Vec<u8>,
+}
+
+//ii Sink for SharedSink
+impl Sink for SharedSink {
+    fn write_indent(&mut self, _depth: usize, indent: &str) -> std::io::Result<()> {
+        self.buffer.extend_from_slice(indent.as_bytes());
+        Ok(())
+    }
+    fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.buffer.extend_from_slice(text.as_bytes());
+        Ok(())
+    }
+    fn write_newline(&mut self, ending: &str) -> std::io::Result<()> {
+        self.buffer.extend_from_slice(ending.as_bytes());
+        Ok(())
+    }
+    fn write_page_break(&mut self, separator: &str, header: Option<&str>) -> std::io::Result<()> {
+        self.buffer.extend_from_slice(separator.as_bytes());
+        if let Some(header) = header {
+            self.buffer.extend_from_slice(header.as_bytes());
+        }
+        Ok(())
+    }
+    fn end_block(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        // A panic in another indenter while writing does not make the
+        // writer unusable
+        let mut writer = match self.target.writer.lock() {
+            Ok(writer) => writer,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let result = writer.write_all(&self.buffer).and_then(|_| writer.flush());
+        self.buffer.clear();
+        result
+    }
+}
@@ -19,12 +19,12 @@ limitations under the License.
 //a Test
 #[cfg(test)]
 mod test {
-    use crate::{IndentedDisplay, IndentedOptions, Indenter};
+    use crate::{DepthChange, IndentedDisplay, IndentedOptions, Indenter};
     use std::fmt::{Display, Write};
     struct Options {
         ind_leaves: bool,
     }
-    impl IndentedOptions<'_> for Options {}
+    impl IndentedOptions for Options {}
     struct Leaf<T: Display + Sized> {
         t: T,
     }
@@ -166,4 +166,2861 @@ banana apple pear
         println!("{}", r);
         assert_eq!(r, output);
     }
+
+    #[test]
+    fn test_elided() {
+        use crate::Elided;
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"[
+  0,
+  1,
+  … more
+]
+[
+  1,
+  2,
+  … and 3 more
+]
+[
+  1,
+  2,
+]
+"###;
+        Elided::new(0u64.., 2).indent(&mut ind).unwrap();
+        let v = [1u32, 2, 3, 4, 5];
+        Elided::new(v.iter().copied(), 2).indent(&mut ind).unwrap();
+        Elided::new(v[0..2].iter().copied(), 2)
+            .indent(&mut ind)
+            .unwrap();
+
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_elided_options() {
+        use crate::Elided;
+        struct Compact(Option<usize>);
+        impl IndentedOptions for Compact {
+            fn inline_threshold(&self) -> Option<usize> {
+                self.0
+            }
+            fn trailing_commas(&self) -> bool {
+                false
+            }
+            fn slice_indices(&self) -> bool {
+                true
+            }
+        }
+        fn render(threshold: Option<usize>, max: usize) -> String {
+            let options = Compact(threshold);
+            let mut r = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut r, "  ", &options);
+                Elided::new([1u32, 2, 3, 4, 5].iter().copied(), max)
+                    .indent(&mut ind)
+                    .unwrap();
+            }
+            String::from_utf8(r).unwrap()
+        }
+        assert_eq!(render(Some(4), 2), "[0: 1, 1: 2, … and 3 more]\n");
+        assert_eq!(
+            render(Some(4), 5),
+            "[\n  0: 1,\n  1: 2,\n  2: 3,\n  3: 4,\n  4: 5\n]\n"
+        );
+        assert_eq!(render(None, 1), "[\n  0: 1,\n  … and 4 more\n]\n");
+        let options = Compact(Some(8));
+        let mut r = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut r, "  ", &options);
+            [1u32, 2, 3, 4, 5][..1].indent(&mut ind).unwrap();
+            Elided::new([1u32].iter().copied(), 2)
+                .indent(&mut ind)
+                .unwrap();
+        }
+        let r = String::from_utf8(r).unwrap();
+        assert_eq!(r, "[0: 1]\n[0: 1]\n");
+    }
+
+    struct PageOptions {}
+    impl IndentedOptions for PageOptions {
+        fn page_length(&self) -> Option<usize> {
+            Some(3)
+        }
+        fn page_header(&self) -> Option<&str> {
+            Some("Report")
+        }
+    }
+
+    #[test]
+    fn test_pages() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &PageOptions {});
+        let output = "a\nb\n  c\n\x0cReport: config\n  d\n    e\n\x0cReport: config\n  f\n";
+        writeln!(ind, "a\nb").unwrap();
+        {
+            let mut sub = ind.sub_named("config");
+            writeln!(sub, "c").unwrap();
+            writeln!(sub, "d").unwrap();
+            {
+                let mut sub = sub.sub_named("tls");
+                writeln!(sub, "e").unwrap();
+            }
+            sub.page_break();
+            writeln!(sub, "f").unwrap();
+        }
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[derive(Clone, Default)]
+    struct Shared(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct FailingWriter {
+        space: usize,
+    }
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if buf.len() > self.space {
+                return Err(std::io::Error::other("disk full"));
+            }
+            self.space -= buf.len();
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_error_path() {
+        let mut w = FailingWriter { space: 8 };
+        let mut ind = Indenter::new(&mut w, "  ", &PageOptions {});
+        let mut sub = ind.sub_named("config");
+        writeln!(sub, "ok").unwrap();
+        let mut sub = sub.sub_named(format!("servers[{}]", 3));
+        assert!(writeln!(sub, "too much output").is_err());
+        let e = sub.last_error().unwrap();
+        assert_eq!(e.scope_path(), "config > servers[3]");
+        assert_eq!(e.io_error().unwrap().kind(), std::io::ErrorKind::Other);
+        assert_eq!(format!("{}", e), "config > servers[3]: disk full");
+        assert!(sub.last_error().is_none());
+    }
+
+    #[test]
+    fn test_matrix() {
+        use crate::Matrix;
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"grid
+    1 20 3
+  400  5 6
+   -7
+  1.5   -2
+    0 0.25
+"###;
+        let grid = vec![vec![1, 20, 3], vec![400, 5, 6], vec![-7]];
+        writeln!(ind, "grid").unwrap();
+        {
+            let mut sub = ind.sub();
+            Matrix::new(&grid).indent(&mut sub).unwrap();
+            Matrix::new(&[[1.5, -2.0], [0.0, 0.25]])
+                .indent(&mut sub)
+                .unwrap();
+        }
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[cfg(any(feature = "ndarray", feature = "nalgebra"))]
+    struct PrecisionOptions {}
+    #[cfg(any(feature = "ndarray", feature = "nalgebra"))]
+    impl IndentedOptions for PrecisionOptions {
+        fn float_precision(&self) -> Option<usize> {
+            Some(2)
+        }
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_ndarray() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &PrecisionOptions {});
+        let output = "1.00 -2.50\n0.12 10.00\n";
+        ndarray::arr2(&[[1.0, -2.5], [0.125, 10.0]])
+            .indent(&mut ind)
+            .unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn test_nalgebra() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &PrecisionOptions {});
+        let output = "1.00 -2.50\n0.12 10.00\n 3.00\n-4.00\n";
+        nalgebra::Matrix2::new(1.0, -2.5, 0.125, 10.0)
+            .indent(&mut ind)
+            .unwrap();
+        nalgebra::Vector2::new(3.0, -4.0).indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn test_petgraph() {
+        use crate::GraphDisplay;
+        let mut g = petgraph::Graph::<&str, u32>::new();
+        let a = g.add_node("a");
+        let b = g.add_node("b");
+        let c = g.add_node("c");
+        g.add_edge(a, b, 1);
+        g.add_edge(b, c, 2);
+        g.add_edge(c, a, 3);
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"a
+  -1-> b
+b
+  -2-> c
+c
+  -3-> a
+a
+  -1-> b
+    -2-> c
+      -3-> ↩ a
+"###;
+        GraphDisplay::new(&g).indent(&mut ind).unwrap();
+        GraphDisplay::spanning_tree(&g, &[a])
+            .indent(&mut ind)
+            .unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_bytes() {
+        use crate::{Bytes, BytesFormat};
+        let data: Vec<u8> = (0x3cu8..0x50).collect();
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"3c 3d 3e 3f 40 41 42 43 44 45 46 47 48 49 4a 4b
+4c 4d 4e 4f
+  00000000  3c 3d 3e 3f 40 41 42 43  44 45 46 47 48 49 4a 4b  |<=>?@ABCDEFGHIJK|
+  00000010  4c 4d 4e 4f 00                                    |LMNO.|
+TWFu
+TWE=
+TQ==
+"###;
+        Bytes::new(&data).indent(&mut ind).unwrap();
+        {
+            let mut data = data.clone();
+            data.push(0);
+            let mut sub = ind.sub();
+            Bytes::new(&data)
+                .with_format(BytesFormat::HexAscii)
+                .indent(&mut sub)
+                .unwrap();
+        }
+        for s in ["Man", "Ma", "M"] {
+            Bytes::new(s)
+                .with_format(BytesFormat::Base64)
+                .indent(&mut ind)
+                .unwrap();
+        }
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[cfg(feature = "bitflags")]
+    bitflags::bitflags! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        struct Perms: u8 {
+            const READ = 1;
+            const WRITE = 2;
+            const EXEC = 4;
+        }
+    }
+
+    #[cfg(feature = "bitflags")]
+    #[test]
+    fn test_bitflags() {
+        use crate::FlagsDisplay;
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"perms
+  READ
+  EXEC
+READ | WRITE | 0x10
+(empty)
+"###;
+        let p = Perms::READ | Perms::EXEC;
+        writeln!(ind, "perms").unwrap();
+        FlagsDisplay::new(&p).indent(&mut ind.sub()).unwrap();
+        let p = Perms::READ | Perms::WRITE | Perms::from_bits_retain(0x10);
+        FlagsDisplay::new(&p).compact().indent(&mut ind).unwrap();
+        FlagsDisplay::new(&Perms::empty())
+            .compact()
+            .indent(&mut ind)
+            .unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_maps() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"{
+  apple: 1,
+  pear: 2,
+}
+{
+  1,
+  3,
+}
+"###;
+        let m: std::collections::BTreeMap<&str, u32> =
+            [("pear", 2), ("apple", 1)].iter().copied().collect();
+        m.indent(&mut ind).unwrap();
+        let s: std::collections::BTreeSet<u32> = [3, 1].iter().copied().collect();
+        s.indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn test_indexmap() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"{
+  pear: 2,
+  apple: 1,
+}
+{
+  3,
+  1,
+}
+"###;
+        let m: indexmap::IndexMap<&str, u32> =
+            [("pear", 2), ("apple", 1)].iter().copied().collect();
+        m.indent(&mut ind).unwrap();
+        let s: indexmap::IndexSet<u32> = [3, 1].iter().copied().collect();
+        s.indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[cfg(all(feature = "smallvec", feature = "arrayvec"))]
+    #[test]
+    fn test_smallvec_arrayvec() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = "[\n  1,\n  2,\n]\n[\n  3,\n]\n";
+        let s: smallvec::SmallVec<[u32; 4]> = smallvec::smallvec![1, 2];
+        s.indent(&mut ind).unwrap();
+        let mut a = arrayvec::ArrayVec::<u32, 4>::new();
+        a.push(3);
+        a.indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_option_result() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"Some:
+  Ok:
+    1
+None
+Ok:
+  3
+Err:
+  failed
+"###;
+        Some(Ok::<u32, &str>(1)).indent(&mut ind).unwrap();
+        None::<u32>.indent(&mut ind).unwrap();
+        writeln!(ind).unwrap();
+        Ok::<u32, &str>(3).indent(&mut ind).unwrap();
+        Err::<u32, &str>("failed").indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_option_result_collection() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"[
+  Some:
+    1
+  ,
+  None,
+]
+[
+  Ok:
+    2
+  ,
+  Err:
+    failed
+  ,
+]
+"###;
+        [Some(1u32), None][..].indent(&mut ind).unwrap();
+        [Ok::<u32, &str>(2), Err("failed")][..]
+            .indent(&mut ind)
+            .unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+        let options = Options { ind_leaves: false };
+        let estimate = Err::<u32, &str>("failed").estimated_size(&options);
+        assert_eq!(estimate, Some(crate::SizeEstimate { lines: 2, width: 6 }));
+    }
+
+    #[cfg(feature = "either")]
+    #[test]
+    fn test_either() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = "Left:\n  1\nRight:\n  two\n";
+        either::Either::<u32, &str>::Left(1)
+            .indent(&mut ind)
+            .unwrap();
+        either::Either::<u32, &str>::Right("two")
+            .indent(&mut ind)
+            .unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_locks() {
+        let m = std::sync::Mutex::new(1u32);
+        let l = std::sync::RwLock::new(2u32);
+        let p = std::sync::Arc::new(std::sync::Mutex::new(3u32));
+        {
+            let p = p.clone();
+            let _ = std::thread::spawn(move || {
+                let _guard = p.lock().unwrap();
+                panic!("poison the mutex");
+            })
+            .join();
+        }
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = "1 2 <locked> <locked> <poisoned>\n";
+        m.indent(&mut ind).unwrap();
+        write!(ind, " ").unwrap();
+        l.indent(&mut ind).unwrap();
+        write!(ind, " ").unwrap();
+        {
+            let _guard = m.lock().unwrap();
+            m.indent(&mut ind).unwrap();
+        }
+        write!(ind, " ").unwrap();
+        {
+            let _guard = l.write().unwrap();
+            l.indent(&mut ind).unwrap();
+        }
+        write!(ind, " ").unwrap();
+        p.indent(&mut ind).unwrap();
+        writeln!(ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_cells() {
+        let c = std::cell::Cell::new(1u32);
+        let rc = std::cell::RefCell::new(2u32);
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = "1 2 <borrowed>\n";
+        c.indent(&mut ind).unwrap();
+        write!(ind, " ").unwrap();
+        rc.indent(&mut ind).unwrap();
+        write!(ind, " ").unwrap();
+        {
+            let _b = rc.borrow_mut();
+            rc.indent(&mut ind).unwrap();
+        }
+        writeln!(ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    struct Tls {
+        #[indent(prefix = "├─ ")]
+        cert: &'static str,
+        #[indent(prefix = "└─ ")]
+        key: &'static str,
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    struct Server {
+        name: String,
+        port: u16,
+        tls: Option<Tls>,
+    }
+
+    #[test]
+    fn test_derive() {
+        let server = Server {
+            name: "main".into(),
+            port: 443,
+            tls: Some(Tls {
+                cert: "a.pem",
+                key: "a.key",
+            }),
+        };
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"Server {
+  name: main,
+  port: 443,
+  tls: Some:
+    Tls {
+    ├─ cert: a.pem,
+    └─ key: a.key,
+    }
+  ,
+}
+"###;
+        server.indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    #[indent(options = "Options")]
+    struct Internals {
+        name: &'static str,
+        #[indent(if = "opts.ind_leaves")]
+        id: u32,
+    }
+
+    #[test]
+    fn test_derive_condition() {
+        let x = Internals { name: "x", id: 3 };
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        x.indent(&mut ind).unwrap();
+        drop(ind);
+        assert_eq!(
+            std::str::from_utf8(&r).unwrap(),
+            "Internals {\n  name: x,\n}\n"
+        );
+
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: true });
+        x.indent(&mut ind).unwrap();
+        drop(ind);
+        assert_eq!(
+            std::str::from_utf8(&r).unwrap(),
+            "Internals {\n  name: x,\n  id: 3,\n}\n"
+        );
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    struct Named<'s, T>
+    where
+        T: Copy,
+    {
+        name: &'s str,
+        value: T,
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    #[indent(bound = "T: IndentedDisplay<'indent, IndentOpt>")]
+    struct Tree<T> {
+        value: T,
+        children: Vec<Tree<T>>,
+    }
+
+    impl<'a, Opt: IndentedOptions, T: IndentedDisplay<'a, Opt>> IndentedDisplay<'a, Opt>
+        for Vec<Tree<T>>
+    {
+        fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+            self.as_slice().indent(f)
+        }
+    }
+
+    #[test]
+    fn test_derive_generics() {
+        let name = String::from("n");
+        let x = Named {
+            name: &name,
+            value: 4u8,
+        };
+        let t = Tree {
+            value: 1u32,
+            children: vec![Tree {
+                value: 2u32,
+                children: vec![],
+            }],
+        };
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"Named {
+  name: n,
+  value: 4,
+}
+Tree {
+  value: 1,
+  children: [
+    Tree {
+      value: 2,
+      children: [
+      ]
+      ,
+    }
+    ,
+  ]
+  ,
+}
+"###;
+        x.indent(&mut ind).unwrap();
+        t.indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    struct Unit;
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    struct Pair(u32, &'static str);
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    #[indent(indexed)]
+    struct IndexedPair(u32, &'static str);
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    #[indent(layout = "inline")]
+    struct InlinePair(u32, &'static str);
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    #[indent(layout = "inline")]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    #[indent(layout = "inline")]
+    struct Span {
+        start: Point,
+        tags: std::collections::BTreeSet<u32>,
+        label: Option<&'static str>,
+    }
+
+    #[test]
+    fn test_derive_inline_fields() {
+        let span = Span {
+            start: Point { x: 1, y: 2 },
+            tags: [7, 8, 9].iter().copied().collect(),
+            label: Some("main"),
+        };
+        let mut r = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+            writeln!(ind, "spans:").unwrap();
+            let mut sub = ind.sub();
+            [span][..].indent(&mut sub).unwrap();
+        }
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(
+            r,
+            "spans:\n  [\n    Span { start: Point { x: 1, y: 2 }, tags: {… 3 elements}, label: Some: main },\n  ]\n"
+        );
+    }
+
+    #[test]
+    fn test_derive_layouts() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = r###"Unit
+Pair(
+  1,
+  a,
+)
+IndexedPair(
+  0: 2,
+  1: b,
+)
+InlinePair(3, c)
+Point { x: 4, y: -5 }
+"###;
+        Unit.indent(&mut ind).unwrap();
+        writeln!(ind).unwrap();
+        Pair(1, "a").indent(&mut ind).unwrap();
+        IndexedPair(2, "b").indent(&mut ind).unwrap();
+        InlinePair(3, "c").indent(&mut ind).unwrap();
+        writeln!(ind).unwrap();
+        Point { x: 4, y: -5 }.indent(&mut ind).unwrap();
+        writeln!(ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        println!("{}", r);
+        assert_eq!(r, output);
+    }
+
+    #[derive(indent_display_derive::IndentedDisplay)]
+    #[indent(doc_labels)]
+    struct Config {
+        /// Listen port
+        ///
+        /// The TCP port to listen on
+        port: u16,
+        workers: u8,
+    }
+
+    #[test]
+    fn test_derive_doc_labels() {
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let output = "Config {\n  Listen port: 80,\n  workers: 4,\n}\n";
+        Config {
+            port: 80,
+            workers: 4,
+        }
+        .indent(&mut ind)
+        .unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, output);
+    }
+
+    #[test]
+    fn test_as_display() {
+        use crate::AsDisplay;
+        let mut r = Vec::new();
+        let mut ind = Indenter::new(&mut r, "  ", &Options { ind_leaves: false });
+        let ip = AsDisplay(std::net::Ipv4Addr::new(10, 0, 0, 1));
+        Some(AsDisplay(1.5f64)).indent(&mut ind).unwrap();
+        ip.indent(&mut ind).unwrap();
+        drop(ind);
+        let r = std::str::from_utf8(&r).unwrap();
+        assert_eq!(r, "Some:\n  1.5\n10.0.0.1");
+    }
+
+    #[test]
+    fn test_indent_to() {
+        let mut r = Vec::new();
+        [1u32, 2]
+            .indent_to(&mut r, "  ", &Options { ind_leaves: false })
+            .unwrap();
+        assert_eq!(std::str::from_utf8(&r).unwrap(), "[\n  1,\n  2,\n]\n");
+
+        let mut w = FailingWriter { space: 4 };
+        let e = [1u32, 2]
+            .indent_to(&mut w, "  ", &Options { ind_leaves: false })
+            .unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Other);
+        assert_eq!(e.to_string(), "disk full");
+    }
+
+    #[test]
+    fn test_rendered_lines() {
+        let options = Options { ind_leaves: false };
+        let lines: Vec<String> = [1u32, 2].rendered_lines("  ", &options).collect();
+        assert_eq!(lines, vec!["[", "  1,", "  2,", "]"]);
+        let lines: Vec<String> = "a\nb".rendered_lines("  ", &options).collect();
+        assert_eq!(lines, vec!["a", "b"]);
+    }
+
+    //fi test_indented_structure
+    #[test]
+    fn test_indented_structure() {
+        let options = Options { ind_leaves: false };
+        let lines = [1u32, 2].indented_structure(&options);
+        assert_eq!(
+            lines,
+            vec![
+                (0, "[".to_string()),
+                (1, "1,".to_string()),
+                (1, "2,".to_string()),
+                (0, "]".to_string()),
+            ]
+        );
+    }
+
+    //fi test_muted
+    #[test]
+    fn test_muted() {
+        use std::fmt::Write;
+        let options = Options { ind_leaves: false };
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "shown").unwrap();
+            {
+                let mut sub = ind.muted();
+                assert!(sub.is_muted());
+                writeln!(sub, "hidden").unwrap();
+                let mut sub2 = sub.sub();
+                writeln!(sub2, "hidden too").unwrap();
+            }
+            assert!(!ind.is_muted());
+            writeln!(ind, "shown again").unwrap();
+            ind.set_enabled(false);
+            writeln!(ind, "disabled").unwrap();
+            ind.set_enabled(true);
+            writeln!(ind, "enabled").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "shown\nshown again\nenabled\n"
+        );
+    }
+
+    //fi test_observe_depth
+    #[test]
+    fn test_observe_depth() {
+        use std::fmt::Write;
+        let options = Options { ind_leaves: false };
+        let events = std::cell::RefCell::new(Vec::new());
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            ind.observe_depth(|c, d, n| events.borrow_mut().push((c, d, n.map(String::from))));
+            writeln!(ind, "top").unwrap();
+            let mut sub = ind.sub_named("config");
+            {
+                let _sub2 = sub.sub();
+            }
+        }
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (DepthChange::Push, 1, Some("config".to_string())),
+                (DepthChange::Push, 2, None),
+                (DepthChange::Pop, 1, None),
+                (DepthChange::Pop, 0, Some("config".to_string())),
+            ]
+        );
+    }
+
+    //fi test_current_path
+    #[test]
+    fn test_current_path() {
+        let options = Options { ind_leaves: false };
+        let mut out = Vec::new();
+        let mut ind = Indenter::new(&mut out, "  ", &options);
+        assert!(ind.current_path().is_empty());
+        let mut sub = ind.sub_named("config");
+        let mut sub2 = sub.sub();
+        let sub3 = sub2.push_named("- ", format!("servers[{}]", 3));
+        assert_eq!(sub3.current_path(), vec!["config", "servers[3]"]);
+        drop(sub3);
+        assert_eq!(sub2.current_path(), vec!["config"]);
+    }
+
+    //fi test_scope_filter
+    #[test]
+    fn test_scope_filter() {
+        struct FilterOptions(crate::ScopeFilter);
+        impl IndentedOptions for FilterOptions {
+            fn scope_filter(&self) -> Option<&crate::ScopeFilter> {
+                Some(&self.0)
+            }
+        }
+        assert!(crate::ScopeFilter::parse("a=maybe").is_err());
+        let filter =
+            crate::ScopeFilter::parse("servers=off, servers.*.tls=on, metrics=off").unwrap();
+        assert_eq!(filter.enabled(&["servers", "main"]), Some(false));
+        assert_eq!(filter.enabled(&["servers", "main", "tls"]), Some(true));
+        assert_eq!(filter.enabled(&["other"]), None);
+        let options = FilterOptions(filter);
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            let mut servers = ind.sub_named("servers");
+            writeln!(servers, "servers").unwrap();
+            let mut main = servers.sub_named("main");
+            writeln!(main, "main").unwrap();
+            let mut tls = main.sub_named("tls");
+            writeln!(tls, "tls").unwrap();
+            drop(tls);
+            drop(main);
+            drop(servers);
+            let mut other = ind.sub_named("other");
+            writeln!(other, "other").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "      tls\n  other\n");
+    }
+
+    //fi test_summary
+    #[test]
+    fn test_summary() {
+        struct SummaryOptions;
+        impl IndentedOptions for SummaryOptions {
+            fn summary_lines(&self) -> Option<usize> {
+                Some(2)
+            }
+            fn summary_marker(&self) -> &str {
+                "… {n} lines, use --verbose"
+            }
+        }
+        let options = SummaryOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            write!(ind, "small {{").unwrap();
+            {
+                let mut sub = ind.sub_named("small");
+                writeln!(sub, "a\nb").unwrap();
+            }
+            writeln!(ind, "}}").unwrap();
+            write!(ind, "big {{").unwrap();
+            {
+                let mut sub = ind.sub_named("big");
+                writeln!(sub, "a\nb\nc").unwrap();
+            }
+            writeln!(ind, "}}").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "small {\n  a\n  b\n}\nbig {\n  … 3 lines, use --verbose\n}\n"
+        );
+    }
+
+    //fi test_max_width
+    #[test]
+    fn test_max_width() {
+        struct WidthOptions(crate::Overflow);
+        impl IndentedOptions for WidthOptions {
+            fn max_width(&self) -> Option<usize> {
+                Some(8)
+            }
+            fn overflow(&self) -> crate::Overflow {
+                self.0
+            }
+        }
+        let render = |overflow| {
+            let options = WidthOptions(overflow);
+            let mut out = Vec::new();
+            let error = {
+                let mut ind = Indenter::new(&mut out, "  ", &options);
+                let mut sub = ind.sub();
+                writeln!(sub, "abcdefghijklmn").unwrap();
+                writeln!(sub, "short").unwrap();
+                sub.last_error()
+            };
+            (String::from_utf8(out).unwrap(), error.is_some())
+        };
+        assert_eq!(
+            render(crate::Overflow::Wrap),
+            ("  abcdef\n  ghijkl\n  mn\n  short\n".to_string(), false)
+        );
+        assert_eq!(
+            render(crate::Overflow::Truncate),
+            ("  abcde…\n  short\n".to_string(), false)
+        );
+        assert_eq!(
+            render(crate::Overflow::Error),
+            ("  abcdefghijklmn\n  short\n".to_string(), true)
+        );
+    }
+
+    //fi test_truncate_graphemes
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn test_truncate_graphemes() {
+        struct WidthOptions;
+        impl IndentedOptions for WidthOptions {
+            fn max_width(&self) -> Option<usize> {
+                Some(4)
+            }
+            fn overflow(&self) -> crate::Overflow {
+                crate::Overflow::Truncate
+            }
+            fn truncation_marker(&self) -> &str {
+                "..."
+            }
+        }
+        let options = WidthOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            // 'e' with a combining acute accent, then a family emoji
+            writeln!(ind, "e\u{301}\u{1f468}\u{200d}\u{1f469}abc").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "e\u{301}...\n");
+    }
+
+    //fi test_pad_width
+    #[test]
+    fn test_pad_width() {
+        struct PadOptions;
+        impl IndentedOptions for PadOptions {
+            fn pad_width(&self) -> Option<usize> {
+                Some(6)
+            }
+        }
+        let options = PadOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "ab").unwrap();
+            let mut sub = ind.sub();
+            writeln!(sub, "abcdefgh").unwrap();
+            write!(sub, "c").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ab    \n  abcdefgh\n  c   \n"
+        );
+    }
+
+    //fi test_wrap_markers
+    #[test]
+    fn test_wrap_markers() {
+        struct WrapOptions;
+        impl IndentedOptions for WrapOptions {
+            fn max_width(&self) -> Option<usize> {
+                Some(8)
+            }
+            fn wrap_end_marker(&self) -> &str {
+                " \\"
+            }
+            fn wrap_start_marker(&self) -> &str {
+                "↪ "
+            }
+        }
+        let options = WrapOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            let mut sub = ind.sub();
+            writeln!(sub, "abcdefghij").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "  abcd \\\n  ↪ ef \\\n  ↪ ghij\n"
+        );
+    }
+
+    //fi test_wrap_breaks
+    #[test]
+    fn test_wrap_breaks() {
+        struct WrapOptions;
+        impl IndentedOptions for WrapOptions {
+            fn max_width(&self) -> Option<usize> {
+                Some(14)
+            }
+            fn wrap_breaks(&self) -> &[&str] {
+                &[", ", "::"]
+            }
+        }
+        let options = WrapOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "std::collections::HashMap").unwrap();
+            write!(ind, "f(alpha, ").unwrap();
+            writeln!(ind, "beta, gamma)").unwrap();
+            writeln!(ind, "abcdefghijklmnop").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "std::\ncollections::\nHashMap\nf(alpha, \nbeta, gamma)\nabcdefghijklmn\nop\n"
+        );
+    }
+
+    //fi test_ratatui
+    #[cfg(feature = "ratatui")]
+    #[test]
+    fn test_ratatui() {
+        use ratatui::style::{Color, Style};
+        let options = Options { ind_leaves: false };
+        let data = [1u32, 2];
+        let bold = Style::default().fg(Color::Red);
+        let text = crate::RatatuiText::new(&data[..])
+            .with_depth_styles(vec![Style::default(), bold])
+            .text("  ", &options);
+        assert_eq!(text.lines.len(), 4);
+        assert_eq!(text.lines[1].spans[0].content, "  ");
+        assert_eq!(text.lines[1].spans[1].content, "1,");
+        assert_eq!(text.lines[1].spans[1].style, bold);
+        assert_eq!(text.lines[3].spans[1].content, "]");
+        assert_eq!(text.lines[3].spans[1].style, Style::default());
+    }
+
+    //fi test_raw_terminal
+    #[test]
+    fn test_raw_terminal() {
+        struct RawOptions;
+        impl IndentedOptions for RawOptions {
+            fn raw_terminal(&self) -> bool {
+                true
+            }
+        }
+        let options = RawOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "a").unwrap();
+            let mut sub = ind.sub();
+            write!(sub, "b").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\r\n  b\r\n");
+    }
+
+    //fi test_shared_target_unterminated
+    #[test]
+    fn test_shared_target_unterminated() {
+        let out = Shared::default();
+        let target = crate::SharedTarget::new(out.clone());
+        let options = Options { ind_leaves: false };
+        {
+            let mut a = Indenter::shared(&target, "  ", &options);
+            let mut b = Indenter::shared(&target, "  ", &options);
+            write!(a.block("A {", "}"), "a1").unwrap();
+            writeln!(b.block("B {", "}"), "b1").unwrap();
+            drop(b);
+            drop(a);
+        }
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(text, "B {\n  b1\n}\nA {\n  a1\n}\n");
+    }
+
+    //fi test_shared_target
+    #[test]
+    fn test_shared_target() {
+        let out = Shared::default();
+        let target = crate::SharedTarget::new(out.clone());
+        let threads: Vec<_> = (0..4)
+            .map(|n| {
+                let target = target.clone();
+                std::thread::spawn(move || {
+                    let options = Options { ind_leaves: false };
+                    let mut ind = Indenter::shared(&target, "  ", &options);
+                    for i in 0..20 {
+                        writeln!(ind, "worker {} {{", n).unwrap();
+                        {
+                            let mut sub = ind.sub();
+                            writeln!(sub, "item {}", i).unwrap();
+                            std::thread::yield_now();
+                            writeln!(sub, "more {}", n).unwrap();
+                        }
+                        writeln!(ind, "}}").unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4 * 20 * 4);
+        for block in lines.chunks(4) {
+            let n = &block[0]["worker ".len()..block[0].len() - 2];
+            assert!(block[1].starts_with("  item "));
+            assert_eq!(block[2], format!("  more {}", n));
+            assert_eq!(block[3], "}");
+        }
+    }
+
+    //fi test_sync_indenter
+    #[test]
+    fn test_sync_indenter() {
+        fn is_send_sync<T: Send + Sync>(_: &T) {}
+        let options = Options { ind_leaves: false };
+        let out = Shared::default();
+        let sync = crate::SyncIndenter::new(out.clone(), "  ", &options);
+        is_send_sync(&sync);
+        std::thread::scope(|s| {
+            for n in 0..4 {
+                let sync = sync.clone();
+                s.spawn(move || {
+                    for i in 0..10 {
+                        sync.with(|ind| {
+                            writeln!(ind, "worker {} {{", n).unwrap();
+                            writeln!(ind.sub(), "item {}", i).unwrap();
+                            writeln!(ind, "}}").unwrap();
+                        });
+                    }
+                });
+            }
+        });
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4 * 10 * 3);
+        for block in lines.chunks(3) {
+            assert!(block[0].starts_with("worker "));
+            assert!(block[1].starts_with("  item "));
+            assert_eq!(block[2], "}");
+        }
+    }
+
+    //fi test_async_indenter
+    #[cfg(feature = "futures-io")]
+    #[test]
+    fn test_async_indenter() {
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+        #[derive(Default)]
+        struct Chunks(Vec<String>);
+        impl futures_io::AsyncWrite for Chunks {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                _cx: &mut Context,
+                buf: &[u8],
+            ) -> Poll<std::io::Result<usize>> {
+                self.0.push(String::from_utf8(buf.to_vec()).unwrap());
+                Poll::Ready(Ok(buf.len()))
+            }
+            fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+            fn poll_close(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+        fn block_on<F: Future>(f: F) -> F::Output {
+            let mut f = std::pin::pin!(f);
+            let mut cx = Context::from_waker(std::task::Waker::noop());
+            match f.as_mut().poll(&mut cx) {
+                Poll::Ready(x) => x,
+                Poll::Pending => panic!("the writer never blocks"),
+            }
+        }
+        let options = crate::NullOptions {};
+        let chunks = block_on(async {
+            let mut ind = crate::AsyncIndenter::new(Chunks::default(), "  ", &options);
+            ind.display(&"header\n").await?;
+            ind.push("> ");
+            ind.display_async(&vec![1u32, 2, 3]).await?;
+            ind.pop();
+            ind.display(&"footer\n").await?;
+            ind.finish().await
+        })
+        .unwrap();
+        assert!(chunks.0.len() > 4);
+        assert_eq!(
+            chunks.0.concat(),
+            "header\n> [\n>   1,\n>   2,\n>   3,\n> ]\nfooter\n"
+        );
+        struct Inline;
+        impl IndentedOptions for Inline {
+            fn inline_threshold(&self) -> Option<usize> {
+                Some(3)
+            }
+        }
+        for x in [&[1u64, 2][..], &[1, 2, 3], &[], &[1, 123456789012345678]] {
+            let streamed = block_on(async {
+                let mut ind = crate::AsyncIndenter::new(Chunks::default(), "  ", &Inline);
+                ind.display_async(x).await?;
+                ind.finish().await
+            });
+            let rendered = block_on(async {
+                let mut ind = crate::AsyncIndenter::new(Chunks::default(), "  ", &Inline);
+                ind.display(x).await?;
+                ind.finish().await
+            });
+            assert_eq!(streamed.unwrap().0.concat(), rendered.unwrap().0.concat());
+        }
+    }
+
+    //fi test_merge
+    #[test]
+    fn test_merge() {
+        let handles: Vec<_> = (0..3u32)
+            .map(|n| {
+                std::thread::spawn(move || {
+                    let options = Options { ind_leaves: false };
+                    let data = [n, n * 10];
+                    crate::RenderedBlock::render(&data[..], &options)
+                })
+            })
+            .collect();
+        let options = Options { ind_leaves: false };
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "results:").unwrap();
+            let mut sub = ind.sub();
+            sub.merge(handles.into_iter().map(|h| h.join().unwrap()))
+                .unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "results:\n  [\n    0,\n    0,\n  ]\n  [\n    1,\n    10,\n  ]\n  [\n    2,\n    20,\n  ]\n"
+        );
+    }
+
+    //fi test_par_slice
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_slice() {
+        let options = Options { ind_leaves: false };
+        let data: Vec<u32> = (0..1000).collect();
+        let mut expected = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut expected, "  ", &options);
+            data[..].indent(&mut ind).unwrap();
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            crate::ParSlice::new(&data)
+                .with_chunk_size(7)
+                .indent(&mut ind)
+                .unwrap();
+        }
+        assert_eq!(out, expected);
+
+        struct ListOptions;
+        impl IndentedOptions for ListOptions {
+            fn slice_indices(&self) -> bool {
+                true
+            }
+            fn trailing_commas(&self) -> bool {
+                false
+            }
+            fn inline_threshold(&self) -> Option<usize> {
+                Some(4)
+            }
+        }
+        let options = ListOptions;
+        for len in [0, 3, 4, 100].iter().copied() {
+            let data = &data[..len];
+            let mut expected = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut expected, "  ", &options);
+                data.indent(&mut ind).unwrap();
+            }
+            let mut out = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut out, "  ", &options);
+                crate::ParSlice::new(data)
+                    .with_chunk_size(7)
+                    .indent(&mut ind)
+                    .unwrap();
+            }
+            assert_eq!(out, expected);
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            crate::ParSlice::new(&data[..3]).indent(&mut ind).unwrap();
+            crate::ParSlice::new(&data[..4])
+                .with_chunk_size(3)
+                .indent(&mut ind)
+                .unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[0: 0, 1: 1, 2: 2]\n[\n  0: 0,\n  1: 1,\n  2: 2,\n  3: 3\n]\n"
+        );
+    }
+
+    //fi test_sort_unordered
+    #[test]
+    fn test_sort_unordered() {
+        struct SortOptions;
+        impl IndentedOptions for SortOptions {
+            fn sort_unordered(&self) -> bool {
+                true
+            }
+        }
+        let options = SortOptions;
+        let map: std::collections::HashMap<&str, u32> = [("c", 3), ("a", 1), ("b", 2), ("d", 4)]
+            .iter()
+            .copied()
+            .collect();
+        let set: std::collections::HashSet<u32> = [30, 10, 20].iter().copied().collect();
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            map.indent(&mut ind).unwrap();
+            set.indent(&mut ind).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\n  a: 1,\n  b: 2,\n  c: 3,\n  d: 4,\n}\n{\n  10,\n  20,\n  30,\n}\n"
+        );
+    }
+
+    //fi test_redact
+    #[test]
+    fn test_redact() {
+        use std::borrow::Cow;
+        #[derive(indent_display_derive::IndentedDisplay)]
+        struct Login {
+            user: &'static str,
+            password: &'static str,
+        }
+        struct RedactOptions;
+        impl IndentedOptions for RedactOptions {
+            fn redact(&self, path: &[String], _value: &str) -> Option<Cow<'_, str>> {
+                if path.last().map(|s| s.as_str()) == Some("password") {
+                    Some("****".into())
+                } else {
+                    None
+                }
+            }
+        }
+        let options = RedactOptions;
+        let login = Login {
+            user: "admin",
+            password: "hunter2",
+        };
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            login.indent(&mut ind).unwrap();
+            let mut sub = ind.sub_named("password");
+            "secret".indent(&mut sub).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Login {\n  user: admin,\n  password: ****,\n}\n  ****\n"
+        );
+    }
+
+    //fi test_elide_empty
+    #[test]
+    fn test_elide_empty() {
+        #[derive(indent_display_derive::IndentedDisplay)]
+        struct Sparse {
+            name: &'static str,
+            alias: Option<u32>,
+            comment: &'static str,
+            tags: std::collections::BTreeMap<&'static str, &'static str>,
+        }
+        struct ElideOptions;
+        impl IndentedOptions for ElideOptions {
+            fn elide_empty(&self) -> bool {
+                true
+            }
+        }
+        let options = ElideOptions;
+        let sparse = Sparse {
+            name: "x",
+            alias: None,
+            comment: "",
+            tags: [("a", ""), ("b", "c")].iter().copied().collect(),
+        };
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            sparse.indent(&mut ind).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Sparse {\n  name: x,\n  tags: {\n    b: c,\n  }\n  ,\n}\n"
+        );
+    }
+
+    //fi test_numeric_options
+    #[test]
+    fn test_numeric_options() {
+        use crate::Matrix;
+        struct NumericOptions;
+        impl IndentedOptions for NumericOptions {
+            fn float_precision(&self) -> Option<usize> {
+                Some(2)
+            }
+            fn scientific_thresholds(&self) -> Option<(f64, f64)> {
+                Some((1e-3, 1e9))
+            }
+            fn thousands_separator(&self) -> Option<&str> {
+                Some(",")
+            }
+        }
+        let options = NumericOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            [1234567u32, 12][..].indent(&mut ind).unwrap();
+            [-1234.5f64, 0.0, 0.00001, 2.5e10][..]
+                .indent(&mut ind)
+                .unwrap();
+            Matrix::new(&[[1000.0, 2.0]]).indent(&mut ind).unwrap();
+            Matrix::new(&[["alpha", "beta"], ["1.2345", "7"], ["12abc", "12345"]])
+                .indent(&mut ind)
+                .unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[\n  1,234,567,\n  12,\n]\n[\n  -1,234.50,\n  0.00,\n  1.00e-5,\n  2.50e10,\n]\n1,000.00 2.00\nalpha   beta\n 1.23      7\n12abc 12,345\n"
+        );
+    }
+
+    //fi test_icu
+    #[cfg(feature = "icu")]
+    #[test]
+    fn test_icu() {
+        use crate::icu::decimal::DecimalFormatter;
+        use crate::icu::locale::Locale;
+        struct LocaleOptions(DecimalFormatter);
+        impl IndentedOptions for LocaleOptions {
+            fn float_precision(&self) -> Option<usize> {
+                Some(2)
+            }
+            fn decimal_formatter(&self) -> Option<&DecimalFormatter> {
+                Some(&self.0)
+            }
+        }
+        let locale: Locale = "de".parse().unwrap();
+        let options =
+            LocaleOptions(DecimalFormatter::try_new(locale.into(), Default::default()).unwrap());
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            [1234567u32][..].indent(&mut ind).unwrap();
+            [-1234.5f64][..].indent(&mut ind).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[\n  1.234.567,\n]\n[\n  -1.234,50,\n]\n"
+        );
+    }
+
+    //fi test_collection_style
+    #[test]
+    fn test_collection_style() {
+        use std::collections::BTreeMap;
+        struct StyleOptions;
+        impl IndentedOptions for StyleOptions {
+            fn list_brackets(&self) -> (&str, &str) {
+                ("(", ")")
+            }
+            fn trailing_commas(&self) -> bool {
+                false
+            }
+            fn inline_threshold(&self) -> Option<usize> {
+                Some(3)
+            }
+            fn inline_element_width(&self) -> usize {
+                4
+            }
+            fn slice_indices(&self) -> bool {
+                true
+            }
+        }
+        let options = StyleOptions;
+        let map: BTreeMap<_, _> = [(1, "a"), (2, "b")].iter().copied().collect();
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            [1, 2][..].indent(&mut ind).unwrap();
+            [1, 2, 3][..].indent(&mut ind).unwrap();
+            ["short", "longer"][..].indent(&mut ind).unwrap();
+            map.indent(&mut ind).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "(0: 1, 1: 2)\n(\n  0: 1,\n  1: 2,\n  2: 3\n)\n(\n  0: short,\n  1: longer\n)\n{1: a, 2: b}\n"
+        );
+    }
+
+    //fi test_summarize
+    #[test]
+    fn test_summarize() {
+        use std::collections::{BTreeMap, BTreeSet};
+        #[derive(indent_display_derive::IndentedDisplay)]
+        struct Inner {
+            values: BTreeSet<u32>,
+        }
+        #[derive(indent_display_derive::IndentedDisplay)]
+        struct Outer {
+            inner: Inner,
+            map: BTreeMap<u32, BTreeSet<u32>>,
+        }
+        struct DepthOptions(Option<usize>, Option<usize>);
+        impl IndentedOptions for DepthOptions {
+            fn max_depth(&self) -> Option<usize> {
+                self.0
+            }
+            fn summary_lines(&self) -> Option<usize> {
+                self.1
+            }
+            fn inline_threshold(&self) -> Option<usize> {
+                Some(3)
+            }
+        }
+        let outer = Outer {
+            inner: Inner {
+                values: [1, 2, 3].iter().copied().collect(),
+            },
+            map: [(0, vec![4]), (1, vec![5, 6, 7, 8])]
+                .iter()
+                .map(|(k, v)| (*k, v.iter().copied().collect()))
+                .collect(),
+        };
+        let render = |options: &DepthOptions| {
+            let mut out = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut out, "  ", options);
+                outer.indent(&mut ind).unwrap();
+            }
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            render(&DepthOptions(Some(1), None)),
+            "Outer {\n  inner: Inner {\n    values: {… 3 elements},\n  }\n  ,\n  map: {\n    0: {… 1 element},\n    1: {… 4 elements},\n  }\n  ,\n}\n"
+        );
+        assert_eq!(
+            render(&DepthOptions(None, Some(3))),
+            "Outer {\n  inner: Inner {…},\n  map: {… 2 entries},\n}\n"
+        );
+    }
+
+    //fi test_estimated_size
+    #[test]
+    fn test_estimated_size() {
+        use crate::SizeEstimate;
+        use std::cell::Cell;
+        struct Large<'c>(&'c Cell<usize>);
+        impl<'a, O: IndentedOptions> IndentedDisplay<'a, O> for Large<'_> {
+            fn indent(&self, f: &mut Indenter<'a, O>) -> std::fmt::Result {
+                use std::fmt::Write;
+                self.0.set(self.0.get() + 1);
+                for i in 0..100 {
+                    writeln!(f, "line {}", i)?;
+                }
+                Ok(())
+            }
+            fn summarize(&self, f: &mut Indenter<'a, O>) -> std::fmt::Result {
+                use std::fmt::Write;
+                write!(f, "large")
+            }
+            fn estimated_size(&self, _options: &'a O) -> Option<SizeEstimate> {
+                Some(SizeEstimate {
+                    lines: 100,
+                    width: 8,
+                })
+            }
+        }
+        struct SummaryOptions;
+        impl IndentedOptions for SummaryOptions {
+            fn summary_lines(&self) -> Option<usize> {
+                Some(3)
+            }
+            fn inline_threshold(&self) -> Option<usize> {
+                Some(4)
+            }
+        }
+        let options = SummaryOptions;
+        let renders = Cell::new(0);
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            [Large(&renders)][..].indent(&mut ind).unwrap();
+            [1, 2, 3][..].indent(&mut ind).unwrap();
+        }
+        assert_eq!(renders.get(), 0);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[\n  large,\n]\n[1, 2, 3]\n"
+        );
+        assert_eq!(
+            IndentedDisplay::<SummaryOptions>::estimated_size(&[10, 200][..], &options),
+            Some(SizeEstimate { lines: 4, width: 4 })
+        );
+    }
+
+    //fi test_route_scopes
+    #[test]
+    fn test_route_scopes() {
+        use std::fmt::Write;
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        let mut errors = Vec::new();
+        let mut details = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            ind.mirror_scopes(|path| path.iter().any(|n| n == "errors"), &mut errors);
+            ind.divert_scopes(|path| path.iter().any(|n| n == "details"), &mut details);
+            writeln!(ind, "report").unwrap();
+            {
+                let mut sub = ind.sub_named("errors");
+                writeln!(sub, "disk full").unwrap();
+            }
+            {
+                let mut sub = ind.sub_named("details");
+                writeln!(sub, "sector 7").unwrap();
+            }
+            writeln!(ind, "done").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "report\n  disk full\ndone\n"
+        );
+        assert_eq!(String::from_utf8(errors).unwrap(), "  disk full\n");
+        assert_eq!(String::from_utf8(details).unwrap(), "  sector 7\n");
+    }
+
+    //fi test_error_kinds
+    #[test]
+    fn test_error_kinds() {
+        use crate::IndentError;
+        struct LimitOptions(crate::Overflow);
+        impl IndentedOptions for LimitOptions {
+            fn max_width(&self) -> Option<usize> {
+                Some(6)
+            }
+            fn overflow(&self) -> crate::Overflow {
+                self.0
+            }
+            fn max_depth(&self) -> Option<usize> {
+                Some(0)
+            }
+        }
+        let options = LimitOptions(crate::Overflow::Truncate);
+        let mut out = Vec::new();
+        let mut ind = Indenter::new(&mut out, "  ", &options);
+        writeln!(ind, "abcdefghij").unwrap();
+        [1][..].indent(&mut ind).unwrap();
+        assert!(ind.last_error().is_none());
+        let abridgements = ind.take_abridgements();
+        assert!(matches!(
+            abridgements[0].kind(),
+            IndentError::Truncated { width: 10, max: 6 }
+        ));
+        assert!(matches!(
+            abridgements[1].kind(),
+            IndentError::DepthExceeded { depth: 1, max: 0 }
+        ));
+        assert!(ind.take_abridgements().is_empty());
+        drop(ind);
+
+        let options = LimitOptions(crate::Overflow::Error);
+        let mut out = Vec::new();
+        let mut ind = Indenter::new(&mut out, "  ", &options);
+        let mut sub = ind.sub_named("table");
+        writeln!(sub, "abcdefghij").unwrap();
+        let e = sub.last_error().unwrap();
+        assert!(e.kind().is_failure());
+        assert!(e.io_error().is_none());
+        assert_eq!(
+            e.to_string(),
+            "table: line width 12 exceeds maximum width of 6"
+        );
+        assert_eq!(e.into_io_error().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    //fi test_scope_timing
+    #[test]
+    fn test_scope_timing() {
+        use crate::ScopeTiming;
+        struct TimingOptions(ScopeTiming);
+        impl IndentedOptions for TimingOptions {
+            fn scope_timing(&self) -> ScopeTiming {
+                self.0
+            }
+        }
+        let render = |timing| {
+            let options = TimingOptions(timing);
+            let mut out = Vec::new();
+            let timings = {
+                let mut ind = Indenter::new(&mut out, "  ", &options);
+                write!(ind, "server").unwrap();
+                {
+                    let mut sub = ind.sub_named("server");
+                    writeln!(sub, "port: 80").unwrap();
+                    let mut sub = sub.sub_named("tls");
+                    writeln!(sub, "on").unwrap();
+                }
+                writeln!(ind, "end").unwrap();
+                ind.scope_timings()
+            };
+            (String::from_utf8(out).unwrap(), timings)
+        };
+        let (out, timings) = render(ScopeTiming::Headers);
+        let paths: Vec<_> = timings.iter().map(|(p, _)| p.join(" > ")).collect();
+        assert_eq!(paths, ["server > tls", "server"]);
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("server (") && lines[0].ends_with(')'));
+        assert_eq!(&lines[1..], ["  port: 80", "    on", "end"]);
+        let (out, _) = render(ScopeTiming::Summary);
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(&lines[..4], ["server", "  port: 80", "    on", "end"]);
+        assert!(lines[4].starts_with("server > tls: "));
+        assert!(lines[5].starts_with("server: "));
+        assert_eq!(lines.len(), 6);
+        let (out, timings) = render(ScopeTiming::Off);
+        assert_eq!(out, "server\n  port: 80\n    on\nend\n");
+        assert!(timings.is_empty());
+    }
+
+    //fi test_severity
+    #[test]
+    fn test_severity() {
+        use crate::Severity;
+        struct LintOptions;
+        impl IndentedOptions for LintOptions {
+            fn severity_summary(&self) -> bool {
+                true
+            }
+        }
+        let options = LintOptions;
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "config.toml").unwrap();
+            {
+                let mut sub = ind.sub();
+                sub.tagged(Severity::Warn, |f| writeln!(f, "unused key"))
+                    .unwrap();
+                sub.tagged(Severity::Error, |f| writeln!(f, "bad port\nline 3"))
+                    .unwrap();
+                let mut sub = sub.sub_tagged(Severity::Error);
+                writeln!(sub, "missing name").unwrap();
+                assert_eq!(sub.severity_count(Severity::Error), 2);
+            }
+            writeln!(ind, "done").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "config.toml\n  warning: unused key\n  error: bad port\n  error: line 3\n    error: missing name\ndone\n2 errors, 1 warning\n"
+        );
+    }
+
+    //fi test_with_sub
+    #[test]
+    fn test_with_sub() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "a").unwrap();
+            ind.with_sub(|sub| {
+                writeln!(sub, "b")?;
+                sub.with_push("> ", |sub| writeln!(sub, "c"))
+            })
+            .unwrap();
+            writeln!(ind, "d").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\n  b\n  > c\nd\n");
+    }
+
+    #[test]
+    fn test_owned_indent() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let base = "-".repeat(2);
+            let mut ind = Indenter::new(&mut out, base, &options);
+            writeln!(ind, "a").unwrap();
+            for name in ["x", "y"] {
+                let mut sub = ind.push(format!("{}> ", name));
+                writeln!(sub, "b").unwrap();
+                let mut sub = sub.sub();
+                writeln!(sub, "c").unwrap();
+            }
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "a\nx> b\nx> --c\ny> b\ny> --c\n"
+        );
+    }
+
+    #[test]
+    fn test_repeat_char() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::with_char(&mut out, ' ', 4, &options);
+            writeln!(ind, "a").unwrap();
+            let mut sub = ind.push_repeat('.', 2);
+            writeln!(sub, "b").unwrap();
+            let mut sub = sub.sub();
+            writeln!(sub, "c").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\n..b\n..    c\n");
+    }
+
+    #[test]
+    fn test_new_owned() {
+        fn render(x: &[usize]) -> String {
+            let options = crate::NullOptions {};
+            let mut ind = Indenter::new_owned(Vec::new(), "  ", &options);
+            writeln!(ind, "values").unwrap();
+            x.indent(&mut ind.sub()).unwrap();
+            String::from_utf8(ind.into_inner()).unwrap()
+        }
+        assert_eq!(render(&[1, 2]), "values\n  [\n    1,\n    2,\n  ]\n");
+    }
+
+    #[test]
+    fn test_new_fmt() {
+        struct Wrapped(usize, usize);
+        impl std::fmt::Display for Wrapped {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let options = crate::NullOptions {};
+                let mut ind = Indenter::new_fmt(f, "  ", &options);
+                [self.0, self.1][..]
+                    .indent(&mut ind)
+                    .map_err(|_| std::fmt::Error)
+            }
+        }
+        let options = crate::NullOptions {};
+        let mut s = String::new();
+        {
+            let mut ind = Indenter::new_fmt(&mut s, "  ", &options);
+            writeln!(ind, "a").unwrap();
+            writeln!(ind.sub(), "b").unwrap();
+        }
+        assert_eq!(s, "a\n  b\n");
+        assert_eq!(Wrapped(1, 2).to_string(), "[\n  1,\n  2,\n]\n");
+    }
+
+    #[test]
+    fn test_indent_sink() {
+        fn render<'a, T: crate::IndentSink<'a>>(sink: T, options: &'a crate::NullOptions) {
+            let mut ind = Indenter::for_sink(sink, "  ", options);
+            writeln!(ind, "a").unwrap();
+            writeln!(ind.sub(), "b").unwrap();
+        }
+        let options = crate::NullOptions {};
+        let mut s = String::new();
+        render(&mut s, &options);
+        let mut v = Vec::new();
+        render(&mut v, &options);
+        assert_eq!(s, "a\n  b\n");
+        assert_eq!(String::from_utf8(v).unwrap(), s);
+        let mut w = std::io::BufWriter::new(Vec::new());
+        render(&mut w, &options);
+        assert_eq!(String::from_utf8(w.into_inner().unwrap()).unwrap(), s);
+        let mut c = std::io::Cursor::new(Vec::new());
+        render(&mut c, &options);
+        assert_eq!(String::from_utf8(c.into_inner()).unwrap(), s);
+        let shared = Shared::default();
+        render(crate::IoTarget(shared.clone()), &options);
+        assert_eq!(
+            String::from_utf8(shared.0.lock().unwrap().clone()).unwrap(),
+            s
+        );
+    }
+
+    #[test]
+    fn test_finish() {
+        let options = crate::NullOptions {};
+        let mut w = FailingWriter { space: 3 };
+        let mut ind = Indenter::new(&mut w, "  ", &options);
+        write!(ind, "abc").unwrap();
+        drop(ind.sub());
+        let e = ind.finish().unwrap_err();
+        assert_eq!(e.to_string(), "disk full");
+
+        let mut w = FailingWriter { space: 8 };
+        let mut ind = Indenter::new(&mut w, "  ", &options);
+        writeln!(ind, "abc").unwrap();
+        ind.sub().pop();
+        ind.finish().unwrap();
+        assert_eq!(w.space, 4);
+    }
+
+    #[test]
+    fn test_io_write() {
+        use std::io::Write as _;
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            let e = "é\n".as_bytes();
+            ind.write_all(&e[..1]).unwrap();
+            ind.write_all(&e[1..]).unwrap();
+            std::io::copy(&mut &b"a\xffb\nc\n"[..], &mut ind.sub()).unwrap();
+            ind.write_all(&e[..1]).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "é\n  a\u{FFFD}b\n  c\n\u{FFFD}"
+        );
+    }
+
+    #[test]
+    fn test_copy_indented() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "output:").unwrap();
+            let n = crate::copy_indented(&b"a\r\nb\n\nc"[..], &mut ind.sub()).unwrap();
+            assert_eq!(n, 7);
+            writeln!(ind, "done").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "output:\n  a\n  b\n  c\ndone\n"
+        );
+    }
+
+    #[cfg(all(feature = "process", unix))]
+    #[test]
+    fn test_child_output() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        let status = {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "running:").unwrap();
+            let mut command = std::process::Command::new("sh");
+            command.args(["-c", "echo a; echo b; echo c >&2; exit 3"]);
+            crate::ChildOutput::new()
+                .with_stderr_prefix("! ")
+                .run(&mut command, &mut ind.sub())
+                .unwrap()
+        };
+        assert_eq!(status.code(), Some(3));
+        let text = String::from_utf8(out).unwrap();
+        let mut lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.remove(0), "running:");
+        lines.sort();
+        assert_eq!(lines, ["  ! c", "  stdout: a", "  stdout: b"]);
+    }
+
+    #[test]
+    fn test_for_lines() {
+        let options = crate::NullOptions {};
+        let mut lines = Vec::new();
+        {
+            let mut ind = Indenter::for_lines(|l: &str| lines.push(l.to_string()), "  ", &options);
+            writeln!(ind, "a").unwrap();
+            writeln!(ind.sub(), "b\nc").unwrap();
+            write!(ind, "d").unwrap();
+        }
+        assert_eq!(lines, ["a", "  b", "  c", "d"]);
+    }
+
+    #[test]
+    fn test_line_writes() {
+        #[derive(Default)]
+        struct Counting {
+            writes: Vec<String>,
+            flushes: usize,
+        }
+        impl std::io::Write for Counting {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.writes.push(String::from_utf8(buf.to_vec()).unwrap());
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.flushes += 1;
+                Ok(())
+            }
+        }
+        struct X;
+        impl IndentedOptions for X {
+            fn flush_policy(&self) -> crate::FlushPolicy {
+                crate::FlushPolicy::EachLine
+            }
+        }
+        let mut w = Counting::default();
+        {
+            let mut ind = Indenter::new(&mut w, "  ", &crate::NullOptions {});
+            let mut sub = ind.sub_n(3);
+            write!(sub, "a").unwrap();
+            writeln!(sub, "b{}", 1).unwrap();
+            write!(sub, "prompt: ").unwrap();
+            sub.flush().unwrap();
+            assert_eq!(sub.stats().lines, 2);
+        }
+        assert_eq!(w.writes, ["      ab1\n", "      prompt: ", "\n"]);
+        assert_eq!(w.flushes, 1);
+
+        let mut w = Counting::default();
+        {
+            let mut ind = Indenter::new(&mut w, "  ", &X);
+            writeln!(ind, "a\nb").unwrap();
+        }
+        assert_eq!(w.writes, ["a\n", "b\n"]);
+        assert_eq!(w.flushes, 3);
+    }
+
+    #[test]
+    fn test_indenter_dyn() {
+        use crate::{DynOptions, IndentedDisplay, IndenterDyn};
+        struct Point(i32, i32);
+        impl<'a> IndentedDisplay<'a, DynOptions<'a>> for Point {
+            fn indent(&self, f: &mut IndenterDyn<'a>) -> std::fmt::Result {
+                let (open, close) = f.options().list_brackets();
+                writeln!(f, "{}", open)?;
+                writeln!(f.sub(), "{}\n{}", self.0, self.1)?;
+                writeln!(f, "{}", close)
+            }
+        }
+        struct X;
+        impl IndentedOptions for X {
+            fn list_brackets(&self) -> (&str, &str) {
+                ("<", ">")
+            }
+        }
+        let null = crate::NullOptions {};
+        let mut out = String::new();
+        for options in [DynOptions(&null), DynOptions(&X)] {
+            let mut ind = Indenter::for_sink(&mut out, "  ", &options);
+            Point(1, 2).indent(&mut ind).unwrap();
+        }
+        assert_eq!(out, "[\n  1\n  2\n]\n<\n  1\n  2\n>\n");
+    }
+
+    #[test]
+    fn test_default_options() {
+        use crate::IndentedDisplay;
+        struct Pair(u32, u32);
+        impl IndentedDisplay<'_> for Pair {
+            fn indent(&self, f: &mut Indenter<'_>) -> std::fmt::Result {
+                writeln!(f, "pair")?;
+                writeln!(f.sub(), "{}\n{}", self.0, self.1)
+            }
+        }
+        fn render<T: for<'a> IndentedDisplay<'a>>(x: &T) -> String {
+            let mut out = String::new();
+            x.indent(&mut Indenter::for_sink(
+                &mut out,
+                "  ",
+                &crate::NullOptions {},
+            ))
+            .unwrap();
+            out
+        }
+        assert_eq!(render(&Pair(1, 2)), "pair\n  1\n  2\n");
+    }
+
+    #[test]
+    fn test_unit_options() {
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &());
+            [1, 2][..].indent(&mut ind).unwrap();
+        }
+        let mut expected = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut expected, "  ", &crate::NullOptions {});
+            [1, 2][..].indent(&mut ind).unwrap();
+        }
+        assert_eq!(out, expected);
+    }
+
+    //fi test_basic_options
+    #[test]
+    fn test_basic_options() {
+        use crate::{BasicOptions, Severity};
+        let options = BasicOptions::new()
+            .with_indent_width(4)
+            .with_line_ending("\r\n")
+            .with_max_blank_lines(1)
+            .with_color(true);
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "a\n\n\nb").unwrap();
+            let mut sub = ind.sub();
+            sub.tagged(Severity::Error, |f| writeln!(f, "c")).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "a\r\n\r\nb\r\n    \x1b[31merror\x1b[0m: c\r\n"
+        );
+        let options = BasicOptions::new().with_max_width(6);
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind.sub(), "abcdefg").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "  abcd\n  efg\n");
+    }
+
+    //fi test_if_verbose
+    #[test]
+    fn test_if_verbose() {
+        use crate::BasicOptions;
+        fn render(verbosity: usize) -> String {
+            let options = BasicOptions::new().with_verbosity(verbosity);
+            let mut out = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut out, "  ", &options);
+                writeln!(ind, "root").unwrap();
+                let mut children = ind.if_verbose(1);
+                writeln!(children, "child").unwrap();
+                writeln!(children.if_verbose(2), "leaf").unwrap();
+            }
+            String::from_utf8(out).unwrap()
+        }
+        assert_eq!(render(0), "root\n");
+        assert_eq!(render(1), "root\n  child\n");
+        assert_eq!(render(2), "root\n  child\n    leaf\n");
+    }
+
+    //fi test_basic_options_env
+    #[test]
+    fn test_basic_options_env() {
+        use crate::{BasicOptions, IndentStyle};
+        let vars = [
+            ("INDENT_STYLE", "tabs"),
+            ("INDENT_MAX_DEPTH", "3"),
+            ("INDENT_MAX_WIDTH", "wide"),
+        ];
+        let lookup = |name: &str| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.to_string())
+        };
+        let options = BasicOptions::from_lookup(lookup);
+        assert_eq!(
+            options,
+            BasicOptions::new()
+                .with_indent_style(IndentStyle::Tabs)
+                .with_max_depth(3)
+        );
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "a").unwrap();
+            writeln!(ind.sub().sub(), "b").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\n\t\tb\n");
+        let options = BasicOptions::from_lookup(|_| None);
+        assert_eq!(options, BasicOptions::new());
+    }
+
+    #[test]
+    fn test_tee() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        let mut copy = String::new();
+        let mut w = FailingWriter { space: 6 };
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            ind.tee(&mut copy);
+            writeln!(ind, "a").unwrap();
+            ind.tee(&mut w as &mut dyn std::io::Write);
+            writeln!(ind.sub(), "b").unwrap();
+            assert!(writeln!(ind, "cdefgh").is_err());
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\n  b\ncdefgh\n");
+        assert_eq!(copy, "a\n  b\ncdefgh\n");
+    }
+
+    #[test]
+    fn test_flush() {
+        #[derive(Default)]
+        struct FlushCounter {
+            written: Vec<u8>,
+            flushed: Vec<usize>,
+        }
+        impl std::io::Write for FlushCounter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.written.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.flushed.push(self.written.len());
+                Ok(())
+            }
+        }
+        let options = crate::NullOptions {};
+        let mut w = FlushCounter::default();
+        {
+            let mut ind = Indenter::new(&mut w, "  ", &options);
+            write!(ind, "a").unwrap();
+            ind.flush().unwrap();
+            let mut sub = ind.sub();
+            writeln!(sub, "b").unwrap();
+            sub.flush().unwrap();
+        }
+        assert_eq!(w.flushed, vec![1, 6]);
+    }
+
+    #[test]
+    fn test_depth_and_start_of_line() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        let mut ind = Indenter::new(&mut out, "  ", &options);
+        assert_eq!(ind.depth(), 0);
+        assert!(ind.is_start_of_line());
+        write!(ind, "a").unwrap();
+        assert!(!ind.is_start_of_line());
+        let mut sub = ind.sub();
+        assert_eq!(sub.depth(), 1);
+        assert_eq!(sub.sub().depth(), 2);
+        assert!(sub.is_start_of_line());
+        write!(sub, "b\nc").unwrap();
+        assert!(!sub.is_start_of_line());
+        writeln!(sub).unwrap();
+        assert!(sub.is_start_of_line());
+    }
+
+    #[test]
+    fn test_current_prefix() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        let mut ind = Indenter::new(&mut out, "  ", &options);
+        assert_eq!(ind.current_prefix(), "");
+        let mut sub = ind.push("> ");
+        assert_eq!(sub.current_prefix(), "> ");
+        {
+            let mut sub = sub.sub();
+            assert_eq!(sub.current_prefix(), ">   ");
+            let sub = sub.push_repeat('.', 3);
+            assert_eq!(sub.current_prefix(), ">   ...");
+        }
+        assert_eq!(sub.current_prefix(), "> ");
+    }
+
+    #[test]
+    fn test_scoped_options() {
+        struct Padded(Option<usize>);
+        impl IndentedOptions for Padded {
+            fn pad_width(&self) -> Option<usize> {
+                self.0
+            }
+        }
+        let padded = Padded(Some(4));
+        let compact = Padded(None);
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, " ", &padded);
+            writeln!(ind, "a").unwrap();
+            ind.with_options(&compact, |sub| {
+                assert_eq!(sub.options().0, None);
+                writeln!(sub, "b")?;
+                writeln!(sub.sub(), "c")
+            })
+            .unwrap();
+            assert_eq!(ind.options().0, Some(4));
+            writeln!(ind, "d").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a   \n b\n  c\nd   \n");
+    }
+
+    #[test]
+    fn test_set_base_indent() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            writeln!(ind, "all:").unwrap();
+            ind.with_sub(|sub| {
+                sub.set_base_indent("\t");
+                writeln!(sub, "target:")?;
+                sub.with_sub(|sub| writeln!(sub.sub(), "command"))?;
+                sub.with_push("# ", |sub| writeln!(sub.sub(), "comment"))
+            })
+            .unwrap();
+            writeln!(ind.sub().sub(), "done").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "all:\n  target:\n  \t\tcommand\n  # \tcomment\n    done\n"
+        );
+    }
+
+    #[test]
+    fn test_sub_n() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            for depth in [2, 0, 3] {
+                let mut sub = ind.sub_n(depth);
+                assert_eq!(sub.depth(), depth);
+                writeln!(sub, "{}", depth).unwrap();
+            }
+            writeln!(ind, "done").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "    2\n0\n      3\ndone\n");
+    }
+
+    #[test]
+    fn test_verbatim() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            let mut sub = ind.sub();
+            writeln!(sub, "patch:").unwrap();
+            {
+                let mut raw = sub.verbatim();
+                writeln!(raw, "@@ -1 +1 @@\n-old").unwrap();
+                writeln!(raw.sub(), "+new").unwrap();
+            }
+            writeln!(sub, "end").unwrap();
+            drop(sub);
+            writeln!(ind, "done").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "  patch:\n@@ -1 +1 @@\n-old\n+new\n  end\ndone\n"
+        );
+    }
+
+    #[test]
+    fn test_block() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "    ", &options);
+            {
+                let mut block = ind.block("struct Foo {", "}");
+                writeln!(block, "a: usize,").unwrap();
+                let mut inner = block.block("b: Bar {", "},");
+                writeln!(inner, "c: bool,").unwrap();
+            }
+            writeln!(ind, "// done").unwrap();
+            drop(ind.muted().block("hidden {", "}"));
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "struct Foo {\n    a: usize,\n    b: Bar {\n        c: bool,\n    },\n}\n// done\n"
+        );
+    }
+
+    #[test]
+    fn test_headered() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            let mut node = ind.headered("root");
+            writeln!(node.headered("left"), "leaf").unwrap();
+            let mut right = node.headered("right:");
+            write!(right, "leaf").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "root\n  left\n    leaf\n  right:\n    leaf\n"
+        );
+    }
+
+    #[test]
+    fn test_section() {
+        struct Underlined;
+        impl IndentedOptions for Underlined {
+            fn section_underline(&self) -> char {
+                '='
+            }
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &Underlined);
+            let mut config = ind.section("Configuration");
+            writeln!(config, "verbose: true").unwrap();
+            writeln!(config.section("Paths"), "/usr").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Configuration\n=============\n  verbose: true\n  Paths\n  =====\n    /usr\n"
+        );
+    }
+
+    #[test]
+    fn test_write_line() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            ind.write_line("a").unwrap();
+            ind.write_line("b\n").unwrap();
+            let mut sub = ind.sub();
+            sub.lines(["c", "d"]).unwrap();
+            sub.lines((1..=2).map(|n| format!("e{}", n))).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "a\nb\n  c\n  d\n  e1\n  e2\n"
+        );
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        struct Blank(usize);
+        impl IndentedOptions for Blank {
+            fn max_blank_lines(&self) -> usize {
+                self.0
+            }
+        }
+        fn render(max_blank_lines: usize) -> String {
+            let options = Blank(max_blank_lines);
+            let mut out = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut out, "  ", &options);
+                ind.ensure_blank_line().unwrap();
+                write!(ind, "a\n\n\nb").unwrap();
+                ind.ensure_blank_line().unwrap();
+                ind.ensure_blank_line().unwrap();
+                let mut sub = ind.sub();
+                writeln!(sub, "c\n").unwrap();
+                sub.ensure_blank_line().unwrap();
+                writeln!(sub, "d").unwrap();
+            }
+            String::from_utf8(out).unwrap()
+        }
+        assert_eq!(render(0), "a\nb\n\n  c\n\n  d\n");
+        assert_eq!(render(1), "a\n\nb\n\n  c\n\n  d\n");
+        assert_eq!(render(2), "a\n\n\nb\n\n  c\n\n  d\n");
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace() {
+        struct Trimmed;
+        impl IndentedOptions for Trimmed {
+            fn trim_trailing_whitespace(&self) -> bool {
+                true
+            }
+            fn pad_width(&self) -> Option<usize> {
+                Some(8)
+            }
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &Trimmed);
+            writeln!(ind, "a  ").unwrap();
+            let mut sub = ind.sub();
+            write!(sub, "b ").unwrap();
+            write!(sub, " c\t\n   \n").unwrap();
+            writeln!(sub.verbatim(), "d  ").unwrap();
+            write!(sub, "e ").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\n  b  c\n\nd  \n  e\n");
+    }
+
+    #[test]
+    fn test_line_ending() {
+        struct Crlf;
+        impl IndentedOptions for Crlf {
+            fn line_ending(&self) -> &str {
+                "\r\n"
+            }
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &Crlf);
+            writeln!(ind, "a").unwrap();
+            write!(ind.sub(), "b\nc").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\r\n  b\r\n  c\r\n");
+    }
+
+    #[test]
+    fn test_newline_hooks() {
+        struct Inline;
+        impl IndentedOptions for Inline {
+            fn newline_on_push(&self) -> bool {
+                false
+            }
+            fn newline_on_pop(&self) -> bool {
+                false
+            }
+            fn indent_blank_lines(&self) -> bool {
+                true
+            }
+            fn max_blank_lines(&self) -> usize {
+                1
+            }
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &Inline);
+            write!(ind, "a: ").unwrap();
+            write!(ind.sub(), "b\n\nc").unwrap();
+            writeln!(ind, " d").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a: b\n  \n  c d\n");
+    }
+
+    #[test]
+    fn test_newline_hooks_helpers() {
+        struct Inline;
+        impl IndentedOptions for Inline {
+            fn newline_on_push(&self) -> bool {
+                false
+            }
+            fn newline_on_pop(&self) -> bool {
+                false
+            }
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &Inline);
+            write!(ind.headered("header:"), "a").unwrap();
+            writeln!(ind, " b").unwrap();
+            write!(ind.section("Title"), "c").unwrap();
+            writeln!(ind, " d").unwrap();
+            write!(ind.block("{", "}"), "e").unwrap();
+            write!(ind, " f").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "header:\n  a b\nTitle\n-----\n  c d\n{\n  e\n}\n f"
+        );
+    }
+
+    #[test]
+    fn test_push_options_hooks() {
+        struct Newlines(bool);
+        impl IndentedOptions for Newlines {
+            fn newline_on_push(&self) -> bool {
+                self.0
+            }
+            fn newline_on_pop(&self) -> bool {
+                self.0
+            }
+        }
+        let inline = Newlines(false);
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &Newlines(true));
+            write!(ind, "a").unwrap();
+            write!(ind.push_options(&inline), " b").unwrap();
+            write!(ind, " c").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a b c");
+    }
+
+    #[test]
+    fn test_final_newline() {
+        struct Final(crate::FinalNewline);
+        impl IndentedOptions for Final {
+            fn final_newline(&self) -> crate::FinalNewline {
+                self.0
+            }
+        }
+        fn render(final_newline: crate::FinalNewline, text: &str) -> String {
+            let options = Final(final_newline);
+            let mut out = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut out, "  ", &options);
+                writeln!(ind, "a").unwrap();
+                write!(ind.sub(), "{}", text).unwrap();
+            }
+            String::from_utf8(out).unwrap()
+        }
+        use crate::FinalNewline::*;
+        assert_eq!(render(AsWritten, "b"), "a\n  b\n");
+        assert_eq!(render(Always, "b"), "a\n  b\n");
+        assert_eq!(render(Never, "b\n"), "a\n  b");
+        assert_eq!(render(Never, "b\nc"), "a\n  b\n  c");
+
+        let mut out = Vec::new();
+        {
+            let options = Final(Always);
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            write!(ind, "a").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a\n");
+        let mut out = Vec::new();
+        {
+            let options = Final(AsWritten);
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            write!(ind, "a").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_deep_frames() {
+        struct Deep(crate::DeepFrames);
+        impl IndentedOptions for Deep {
+            fn max_depth(&self) -> Option<usize> {
+                Some(1)
+            }
+            fn deep_frames(&self) -> crate::DeepFrames {
+                self.0
+            }
+        }
+        fn render(deep_frames: crate::DeepFrames) -> String {
+            let options = Deep(deep_frames);
+            let mut out = Vec::new();
+            {
+                let mut ind = Indenter::new(&mut out, "  ", &options);
+                for name in ["a", "b"] {
+                    let mut sub = ind.headered(name);
+                    let mut deep = sub.headered("c");
+                    writeln!(deep, "d").unwrap();
+                    writeln!(deep.sub(), "e").unwrap();
+                    drop(deep);
+                    writeln!(sub, "f").unwrap();
+                }
+            }
+            String::from_utf8(out).unwrap()
+        }
+        use crate::DeepFrames::*;
+        assert_eq!(
+            render(Indent),
+            "a\n  c\n    d\n      e\n  f\nb\n  c\n    d\n      e\n  f\n"
+        );
+        assert_eq!(
+            render(Flatten),
+            "a\n  c\n  d\n  e\n  f\nb\n  c\n  d\n  e\n  f\n"
+        );
+        assert_eq!(render(Elide), "a\n  c\n    …\n  f\nb\n  c\n    …\n  f\n");
+    }
+
+    #[test]
+    fn test_max_lines() {
+        struct Budget;
+        impl IndentedOptions for Budget {
+            fn max_lines(&self) -> Option<usize> {
+                Some(3)
+            }
+        }
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &Budget);
+            writeln!(ind, "a").unwrap();
+            write!(ind.sub(), "b\nc").unwrap();
+            write!(ind, "d").unwrap();
+            write!(ind, "e\nf\n").unwrap();
+            writeln!(ind.sub(), "g").unwrap();
+            let abridgements = ind.take_abridgements();
+            assert_eq!(abridgements.len(), 1);
+            assert_eq!(
+                abridgements[0].to_string(),
+                "output exceeds maximum of 3 lines"
+            );
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "a\n  b\n  c\n… output truncated (3 more lines)\n"
+        );
+    }
+
+    #[test]
+    fn test_measure() {
+        use std::collections::{BTreeMap, BTreeSet};
+        let options = crate::NullOptions {};
+        let x: BTreeMap<&str, BTreeSet<usize>> =
+            [("ab", [1, 22].into()), ("c", BTreeSet::new())].into();
+        let metrics = Indenter::measure(&x, "  ", &options);
+        let mut out = Vec::new();
+        x.indent(&mut Indenter::new(&mut out, "  ", &options))
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(metrics.lines, out.lines().count());
+        assert_eq!(
+            metrics.width,
+            out.lines().map(|l| l.chars().count()).max().unwrap()
+        );
+        assert_eq!(metrics.depth, 2);
+        assert_eq!(
+            Indenter::measure(&"", "  ", &options),
+            crate::Metrics::default()
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        let stats = {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            assert_eq!(ind.stats(), crate::RenderStats::default());
+            writeln!(ind, "a").unwrap();
+            writeln!(ind.sub().sub(), "é").unwrap();
+            write!(ind, "b").unwrap();
+            ind.stats()
+        };
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.depth, 2);
+        assert_eq!(stats.bytes, out.len());
+    }
+
+    #[test]
+    fn test_take_io_error() {
+        let mut w = FailingWriter { space: 4 };
+        let mut ind = Indenter::new(&mut w, "  ", &crate::NullOptions {});
+        writeln!(ind, "ok").unwrap();
+        assert!(ind.take_io_error().is_none());
+        assert!(writeln!(ind, "too much output").is_err());
+        let e = ind.take_io_error().unwrap();
+        assert_eq!(e.to_string(), "disk full");
+        assert!(ind.take_io_error().is_none());
+        assert!(ind.last_error().is_none());
+    }
+
+    #[test]
+    fn test_try_indent() {
+        struct Failing;
+        impl<'a, O: IndentedOptions> IndentedDisplay<'a, O> for Failing {
+            fn indent(&self, _f: &mut Indenter<'a, O>) -> std::fmt::Result {
+                Err(std::fmt::Error)
+            }
+        }
+        let options = crate::NullOptions {};
+        let mut w = FailingWriter { space: 4 };
+        let mut ind = Indenter::new(&mut w, "  ", &options);
+        let mut sub = ind.sub_named("data");
+        let e = "too much output\n".try_indent(&mut sub).unwrap_err();
+        assert_eq!(e.to_string(), "data: disk full");
+        assert!(e.io_error().is_some());
+        let e = Failing.try_indent(&mut sub).unwrap_err();
+        assert!(matches!(e.kind(), crate::IndentError::Fmt));
+        drop(sub);
+        drop(ind);
+        let mut out = Vec::new();
+        let mut ind = Indenter::new(&mut out, "  ", &options);
+        let r: crate::Result<()> = "ok\n".try_indent(&mut ind);
+        assert!(r.is_ok());
+    }
+
+    #[test]
+    fn test_sub_pop() {
+        let options = crate::NullOptions {};
+        let mut out = Vec::new();
+        {
+            let mut ind = Indenter::new(&mut out, "  ", &options);
+            let mut sub = ind.sub();
+            let mut subsub = sub.push("> ");
+            writeln!(subsub, "a").unwrap();
+            let sub = subsub.pop();
+            writeln!(sub, "b").unwrap();
+            let mut sub = ind.sub_n(2);
+            writeln!(sub, "c").unwrap();
+            let ind = sub.pop();
+            assert_eq!(ind.depth(), 0);
+            writeln!(ind, "d").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "  > a\n  b\n    c\nd\n");
+    }
 }
@@ -0,0 +1,54 @@
+/*a Copyright
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+  http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+
+@file    text.rs
+@brief   Measurement and splitting of text
+ */
+
+//a Functions
+//fp width
+/// The width of some text, in user-perceived characters
+///
+/// With the `unicode-segmentation` feature this is the number of
+/// grapheme clusters, so that (for example) an emoji with modifiers
+/// or a letter with combining accents counts as one; otherwise it is
+/// the number of Unicode scalar values
+pub(crate) fn width(s: &str) -> usize {
+    #[cfg(feature = "unicode-segmentation")]
+    {
+        use unicode_segmentation::UnicodeSegmentation;
+        s.graphemes(true).count()
+    }
+    #[cfg(not(feature = "unicode-segmentation"))]
+    {
+        s.chars().count()
+    }
+}
+
+//fp split_at_width
+/// Split some text after at most `n` user-perceived characters (as
+/// for [width]), so that a character is never split
+pub(crate) fn split_at_width(s: &str, n: usize) -> (&str, &str) {
+    #[cfg(feature = "unicode-segmentation")]
+    let index = {
+        use unicode_segmentation::UnicodeSegmentation;
+        s.grapheme_indices(true).nth(n).map(|(i, _)| i)
+    };
+    #[cfg(not(feature = "unicode-segmentation"))]
+    let index = s.char_indices().nth(n).map(|(i, _)| i);
+    match index {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    }
+}
@@ -17,18 +17,808 @@ limitations under the License.
  */
 
 //a Imports
-use crate::Indenter;
+use std::borrow::Cow;
+
+use crate::sinks::{DepthRecorder, IoSink, Recorder};
+use crate::{
+    BytesFormat, DeepFrames, Error, FinalNewline, FlushPolicy, IndentStyle, IndentedLines,
+    Indenter, Overflow, ScopeFilter, ScopeTiming, Severity, SizeEstimate,
+};
 
 //a Traits
 //tt IndentedOptions
-pub trait IndentedOptions<'a>: Sized + 'a {}
+/// Options used by an [Indenter]; these may be interrogated by
+/// implementations of [IndentedDisplay], and the provided methods
+/// may be overridden to change the behavior of the indenter itself
+pub trait IndentedOptions {
+    //mp indent_width
+    /// The width in spaces (or tabs, as given by
+    /// [IndentedOptions::indent_style]) of each level of indentation,
+    /// if this replaces the base indentation string given when the
+    /// indenter is created; indentation set with
+    /// [Indenter::set_base_indent] or pushed with [Indenter::push] is
+    /// unaffected
+    fn indent_width(&self) -> Option<usize> {
+        None
+    }
+
+    //mp indent_style
+    /// The character with which each level of indentation is made;
+    /// with tabs, the base indentation string given when the indenter
+    /// is created is replaced even if there is no indent width
+    fn indent_style(&self) -> IndentStyle {
+        IndentStyle::Spaces
+    }
+
+    //mp page_length
+    /// The number of lines per page, if the output is to be paginated
+    fn page_length(&self) -> Option<usize> {
+        None
+    }
+
+    //mp page_separator
+    /// The string output at a page break, before any page header
+    fn page_separator(&self) -> &str {
+        "\x0c"
+    }
+
+    //mp page_header
+    /// The header line for a new page, if any; the current scope
+    /// path is appended to this
+    fn page_header(&self) -> Option<&str> {
+        None
+    }
+
+    //mp raw_terminal
+    /// Return true if the output is to a terminal in raw mode, in
+    /// which case lines are ended with "\r\n" (as a newline alone
+    /// does not return the cursor to the start of the line)
+    ///
+    /// This is invoked at the end of every line, so it may detect the
+    /// mode (for example with crossterm's `is_raw_mode_enabled`)
+    fn raw_terminal(&self) -> bool {
+        false
+    }
+
+    //mp line_ending
+    /// The line ending with which every line of output is ended -
+    /// such as "\r\n" for Windows text files or protocols that
+    /// require it
+    ///
+    /// The default is "\r\n" for a terminal in raw mode (see
+    /// [IndentedOptions::raw_terminal]), otherwise "\n"
+    fn line_ending(&self) -> &str {
+        if self.raw_terminal() {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+
+    //mp newline_on_push
+    /// Return true if pushing a frame (such as with [Indenter::sub])
+    /// ends the current line, so that the output of the frame starts
+    /// on a new line at its indentation
+    ///
+    /// If false, output of the frame continues the current line, and
+    /// only subsequent lines are at its indentation
+    fn newline_on_push(&self) -> bool {
+        true
+    }
+
+    //mp newline_on_pop
+    /// Return true if popping a frame ends the current line, so that
+    /// the output after the frame starts on a new line at the
+    /// indentation of its parent
+    ///
+    /// If false, output after the frame continues the current line
+    fn newline_on_pop(&self) -> bool {
+        true
+    }
+
+    //mp final_newline
+    /// Whether the last line of the output is ended with a newline
+    /// when the output is completed, so that (for example) generated
+    /// files end consistently
+    fn final_newline(&self) -> FinalNewline {
+        FinalNewline::AsWritten
+    }
+
+    //mp flush_policy
+    /// When the writer is flushed; each line is written to the
+    /// writer with a single write when it is ended, but the writer
+    /// (such as a `BufWriter`) may buffer it further
+    fn flush_policy(&self) -> FlushPolicy {
+        FlushPolicy::Manual
+    }
+
+    //mp max_blank_lines
+    /// The maximum number of consecutive blank lines that are output
+    /// from the text written to an indenter; further blank lines are
+    /// dropped
+    ///
+    /// With the default of zero blank lines in the text are dropped;
+    /// with one, runs of blank lines are collapsed to a single blank
+    /// line. A blank line from [Indenter::ensure_blank_line] is
+    /// output whatever this is.
+    fn max_blank_lines(&self) -> usize {
+        0
+    }
+
+    //mp indent_blank_lines
+    /// Return true if blank lines are output with the indentation of
+    /// the current depth; by default they are empty
+    ///
+    /// Blank lines are always empty if trailing whitespace is trimmed
+    /// (see [IndentedOptions::trim_trailing_whitespace]), and within
+    /// verbatim frames
+    fn indent_blank_lines(&self) -> bool {
+        false
+    }
+
+    //mp max_width
+    /// The maximum width of a line of output in characters, including
+    /// its indentation, if limited; lines that would exceed this are
+    /// handled according to [IndentedOptions::overflow]
+    ///
+    /// With the `unicode-segmentation` feature characters are
+    /// grapheme clusters, so lines are never wrapped or truncated
+    /// within one
+    fn max_width(&self) -> Option<usize> {
+        None
+    }
+
+    //mp overflow
+    /// The policy for lines that would exceed the maximum width
+    fn overflow(&self) -> Overflow {
+        Overflow::Wrap
+    }
+
+    //mp wrap_breaks
+    /// The strings after which a line that is wrapped because of the
+    /// maximum width should preferably be broken, such as `","`, `" "`
+    /// or `"::"`; if there are none within the line then it is broken
+    /// at the maximum width
+    fn wrap_breaks(&self) -> &[&str] {
+        &[]
+    }
+
+    //mp wrap_end_marker
+    /// The marker appended to a line that is wrapped because of the
+    /// maximum width, such as `" \\"`; this is included within the
+    /// width
+    fn wrap_end_marker(&self) -> &str {
+        ""
+    }
+
+    //mp wrap_start_marker
+    /// The marker placed after the indentation of the continuation of
+    /// a line that is wrapped because of the maximum width, such as
+    /// `"↪ "`
+    fn wrap_start_marker(&self) -> &str {
+        ""
+    }
+
+    //mp pad_width
+    /// The width in characters to which every line of output is
+    /// padded with spaces, if any; lines that are already as wide (or
+    /// wider) are not changed
+    fn pad_width(&self) -> Option<usize> {
+        None
+    }
+
+    //mp trim_trailing_whitespace
+    /// Return true if trailing whitespace is removed from every line
+    /// of output (other than within verbatim frames), including any
+    /// line that would be only indentation
+    ///
+    /// This takes precedence over the padding of lines to the pad
+    /// width
+    fn trim_trailing_whitespace(&self) -> bool {
+        false
+    }
+
+    //mp truncation_marker
+    /// The marker that ends a line truncated because of the maximum
+    /// width; this is included within the width
+    fn truncation_marker(&self) -> &str {
+        "…"
+    }
+
+    //mp scope_filter
+    /// The filter that determines which named scopes produce output,
+    /// if any; output in scopes that the filter turns off is
+    /// discarded
+    fn scope_filter(&self) -> Option<&ScopeFilter> {
+        None
+    }
+
+    //mp summary_lines
+    /// The maximum number of lines of output of a named scope, if
+    /// limited; a named scope that outputs more is replaced by a
+    /// single summary line
+    ///
+    /// The output of named scopes is buffered if this is set; values
+    /// displayed with [Indenter::value] (such as the elements of
+    /// collections and the fields of derived implementations) that
+    /// would output more are displayed by their
+    /// [IndentedDisplay::summarize] method instead
+    fn summary_lines(&self) -> Option<usize> {
+        None
+    }
+
+    //mp max_lines
+    /// The maximum number of lines of output, if limited; any further
+    /// output is discarded, and a single line noting how many lines
+    /// were discarded is output when the output is completed
+    fn max_lines(&self) -> Option<usize> {
+        None
+    }
+
+    //mp lines_marker
+    /// The template for the line that notes the output discarded
+    /// because of [IndentedOptions::max_lines]
+    ///
+    /// In the template `{n}` is replaced by the number of lines
+    /// discarded
+    fn lines_marker(&self) -> &str {
+        "… output truncated ({n} more lines)"
+    }
+
+    //mp summary_marker
+    /// The template for the line that replaces the output of a named
+    /// scope that exceeds [IndentedOptions::summary_lines]
+    ///
+    /// In the template `{n}` is replaced by the number of lines of
+    /// output of the scope, and `{depth}` by its depth
+    fn summary_marker(&self) -> &str {
+        "… {n} lines"
+    }
+
+    //mp scope_timing
+    /// How (if at all) the wall-clock durations of named scopes are
+    /// reported; the durations are always available from
+    /// [Indenter::scope_timings] if this is not [ScopeTiming::Off]
+    fn scope_timing(&self) -> ScopeTiming {
+        ScopeTiming::Off
+    }
+
+    //mp timing_marker
+    /// The template for the duration of a named scope appended to its
+    /// header line with [ScopeTiming::Headers]
+    ///
+    /// In the template `{t}` is replaced by the duration
+    fn timing_marker(&self) -> &str {
+        " ({t})"
+    }
+
+    //mp verbosity
+    /// The verbosity level of the output; frames created with
+    /// [Indenter::if_verbose] for a higher level are muted
+    fn verbosity(&self) -> usize {
+        0
+    }
+
+    //mp color
+    /// Return true if the output may include terminal color styling;
+    /// this is consulted by the default severity markers, and may be
+    /// by implementations of [IndentedDisplay]
+    fn color(&self) -> bool {
+        false
+    }
+
+    //mp severity_marker
+    /// The marker placed after the indentation of each line tagged
+    /// with a severity; this may include terminal styling
+    ///
+    /// By default the marker is colored if [IndentedOptions::color]
+    /// is true
+    fn severity_marker(&self, severity: Severity) -> &str {
+        match (severity, self.color()) {
+            (Severity::Info, false) => "info: ",
+            (Severity::Warn, false) => "warning: ",
+            (Severity::Error, false) => "error: ",
+            (Severity::Info, true) => "\x1b[36minfo\x1b[0m: ",
+            (Severity::Warn, true) => "\x1b[33mwarning\x1b[0m: ",
+            (Severity::Error, true) => "\x1b[31merror\x1b[0m: ",
+        }
+    }
+
+    //mp severity_summary
+    /// Return true if a summary of the number of tags of each
+    /// severity (such as "3 errors, 7 warnings") is output as the
+    /// last line of the output, if there were any
+    fn severity_summary(&self) -> bool {
+        false
+    }
+
+    //mp section_underline
+    /// The character with which the titles of sections created with
+    /// [Indenter::section] are underlined
+    fn section_underline(&self) -> char {
+        '-'
+    }
+
+    //mp max_depth
+    /// The maximum depth at which values displayed with
+    /// [Indenter::value] are displayed in full, if limited; deeper
+    /// values are displayed by their [IndentedDisplay::summarize]
+    /// method
+    ///
+    /// The output of frames deeper than this is treated as given by
+    /// [IndentedOptions::deep_frames]
+    fn max_depth(&self) -> Option<usize> {
+        None
+    }
+
+    //mp deep_frames
+    /// The treatment of the output of frames deeper than the maximum
+    /// depth, if it is limited; by default it is indented as normal
+    fn deep_frames(&self) -> DeepFrames {
+        DeepFrames::Indent
+    }
+
+    //mp depth_marker
+    /// The marker that replaces the output of frames deeper than the
+    /// maximum depth, if they are elided
+    fn depth_marker(&self) -> &str {
+        "…"
+    }
+
+    //mp sort_unordered
+    /// Return true if the elements of unordered collections (such as
+    /// HashMap and HashSet) are to be sorted before display, so that
+    /// the output is reproducible
+    ///
+    /// The elements are sorted by their own rendering with the
+    /// options (for maps, that of their keys), so they need not
+    /// implement Ord
+    fn sort_unordered(&self) -> bool {
+        false
+    }
+
+    //mp redact
+    /// The redaction hook, which may replace the text of a value
+    /// before it is output, given the scope path at which it is
+    /// output (including any labels, such as the field names of
+    /// derived implementations)
+    ///
+    /// This is consulted for all values displayed using their
+    /// [std::fmt::Display] implementation, so that (for example)
+    /// passwords may be masked centrally; None leaves the value as
+    /// it is
+    fn redact(&self, path: &[String], value: &str) -> Option<Cow<'_, str>> {
+        let _ = (path, value);
+        None
+    }
+
+    //mp elide_empty
+    /// Return true if map entries and derived struct fields whose
+    /// values are empty (see [IndentedDisplay::is_empty_value]) are
+    /// to be omitted
+    fn elide_empty(&self) -> bool {
+        false
+    }
+
+    //mp list_brackets
+    /// The opening and closing brackets of slices (and other lists)
+    fn list_brackets(&self) -> (&str, &str) {
+        ("[", "]")
+    }
+
+    //mp map_brackets
+    /// The opening and closing brackets of maps
+    fn map_brackets(&self) -> (&str, &str) {
+        ("{", "}")
+    }
+
+    //mp set_brackets
+    /// The opening and closing brackets of sets
+    fn set_brackets(&self) -> (&str, &str) {
+        ("{", "}")
+    }
+
+    //mp trailing_commas
+    /// Return true if the last element of a collection displayed one
+    /// element per line is followed by a comma, as the others are
+    fn trailing_commas(&self) -> bool {
+        true
+    }
+
+    //mp inline_threshold
+    /// The number of elements below which a collection is displayed
+    /// on a single line, such as `[1, 2, 3]`, if its elements are all
+    /// short (see [IndentedOptions::inline_element_width]); if None,
+    /// collections are always displayed one element per line
+    fn inline_threshold(&self) -> Option<usize> {
+        None
+    }
+
+    //mp inline_element_width
+    /// The maximum width in characters of an element of a collection
+    /// that is displayed on a single line; elements that are wider,
+    /// or that are displayed over more than one line, prevent this
+    fn inline_element_width(&self) -> usize {
+        16
+    }
+
+    //mp slice_indices
+    /// Return true if the elements of slices are labelled with their
+    /// index, as `0: value`
+    fn slice_indices(&self) -> bool {
+        false
+    }
+
+    //mp elision_marker
+    /// The template for the line used when elements of a collection
+    /// are not shown and the number not shown is known
+    ///
+    /// In the template `{n}` is replaced by the number of elements
+    /// not shown, and `{depth}` by the current indentation depth
+    fn elision_marker(&self) -> &str {
+        "… and {n} more"
+    }
+
+    //mp elision_marker_unknown
+    /// The template for the line used when elements of a collection
+    /// are not shown but the number not shown is unknown
+    fn elision_marker_unknown(&self) -> &str {
+        "… more"
+    }
+
+    //mp float_precision
+    /// The number of digits after the decimal point to use for
+    /// floating point values (and in numeric layouts), if fixed
+    fn float_precision(&self) -> Option<usize> {
+        None
+    }
+
+    //mp scientific_thresholds
+    /// The magnitudes outside of which floating point values are
+    /// displayed in scientific notation, if any, as (small, large):
+    /// non-zero values whose magnitude is less than small, or at
+    /// least large, are displayed as (for example) `1.5e-7`
+    fn scientific_thresholds(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    //mp thousands_separator
+    /// The separator placed between each group of three digits of
+    /// the integer part of numbers, if any, such as `","` or `"_"`
+    fn thousands_separator(&self) -> Option<&str> {
+        None
+    }
+
+    //mp decimal_formatter
+    /// The formatter used to format numbers for a locale (with the
+    /// `icu` feature), if any; this replaces the decimal separator
+    /// and digit grouping (so the thousands separator is not used),
+    /// but numbers in scientific notation are not localized
+    ///
+    /// The formatter is created once by the options, for example with
+    /// `DecimalFormatter::try_new(locale.into(), Default::default())`
+    #[cfg(feature = "icu")]
+    fn decimal_formatter(&self) -> Option<&crate::icu::decimal::DecimalFormatter> {
+        None
+    }
+
+    //mp bytes_format
+    /// The format to use for byte data, unless explicitly chosen
+    fn bytes_format(&self) -> BytesFormat {
+        BytesFormat::Hex
+    }
+}
+
+//a DynOptions
+//tp DynOptions
+/// Options that refer to any [IndentedOptions] through a trait
+/// object, so that an [crate::IndenterDyn] may be used with options of
+/// any type
+///
+/// Implementations of [IndentedDisplay] for `DynOptions` are
+/// compiled once, rather than once for each type of options they are
+/// used with.
+#[derive(Clone, Copy)]
+pub struct DynOptions<'a>(pub &'a dyn IndentedOptions);
+
+//ip Debug for DynOptions
+impl std::fmt::Debug for DynOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "DynOptions")
+    }
+}
+
+//ip IndentedOptions for DynOptions
+impl IndentedOptions for DynOptions<'_> {
+    fn indent_width(&self) -> Option<usize> {
+        self.0.indent_width()
+    }
+    fn indent_style(&self) -> IndentStyle {
+        self.0.indent_style()
+    }
+    fn page_length(&self) -> Option<usize> {
+        self.0.page_length()
+    }
+    fn page_separator(&self) -> &str {
+        self.0.page_separator()
+    }
+    fn page_header(&self) -> Option<&str> {
+        self.0.page_header()
+    }
+    fn raw_terminal(&self) -> bool {
+        self.0.raw_terminal()
+    }
+    fn line_ending(&self) -> &str {
+        self.0.line_ending()
+    }
+    fn newline_on_push(&self) -> bool {
+        self.0.newline_on_push()
+    }
+    fn newline_on_pop(&self) -> bool {
+        self.0.newline_on_pop()
+    }
+    fn final_newline(&self) -> FinalNewline {
+        self.0.final_newline()
+    }
+    fn flush_policy(&self) -> FlushPolicy {
+        self.0.flush_policy()
+    }
+    fn max_blank_lines(&self) -> usize {
+        self.0.max_blank_lines()
+    }
+    fn indent_blank_lines(&self) -> bool {
+        self.0.indent_blank_lines()
+    }
+    fn max_width(&self) -> Option<usize> {
+        self.0.max_width()
+    }
+    fn overflow(&self) -> Overflow {
+        self.0.overflow()
+    }
+    fn wrap_breaks(&self) -> &[&str] {
+        self.0.wrap_breaks()
+    }
+    fn wrap_end_marker(&self) -> &str {
+        self.0.wrap_end_marker()
+    }
+    fn wrap_start_marker(&self) -> &str {
+        self.0.wrap_start_marker()
+    }
+    fn pad_width(&self) -> Option<usize> {
+        self.0.pad_width()
+    }
+    fn trim_trailing_whitespace(&self) -> bool {
+        self.0.trim_trailing_whitespace()
+    }
+    fn truncation_marker(&self) -> &str {
+        self.0.truncation_marker()
+    }
+    fn scope_filter(&self) -> Option<&ScopeFilter> {
+        self.0.scope_filter()
+    }
+    fn summary_lines(&self) -> Option<usize> {
+        self.0.summary_lines()
+    }
+    fn max_lines(&self) -> Option<usize> {
+        self.0.max_lines()
+    }
+    fn lines_marker(&self) -> &str {
+        self.0.lines_marker()
+    }
+    fn summary_marker(&self) -> &str {
+        self.0.summary_marker()
+    }
+    fn scope_timing(&self) -> ScopeTiming {
+        self.0.scope_timing()
+    }
+    fn timing_marker(&self) -> &str {
+        self.0.timing_marker()
+    }
+    fn verbosity(&self) -> usize {
+        self.0.verbosity()
+    }
+    fn color(&self) -> bool {
+        self.0.color()
+    }
+    fn severity_marker(&self, severity: Severity) -> &str {
+        self.0.severity_marker(severity)
+    }
+    fn severity_summary(&self) -> bool {
+        self.0.severity_summary()
+    }
+    fn section_underline(&self) -> char {
+        self.0.section_underline()
+    }
+    fn max_depth(&self) -> Option<usize> {
+        self.0.max_depth()
+    }
+    fn deep_frames(&self) -> DeepFrames {
+        self.0.deep_frames()
+    }
+    fn depth_marker(&self) -> &str {
+        self.0.depth_marker()
+    }
+    fn sort_unordered(&self) -> bool {
+        self.0.sort_unordered()
+    }
+    fn redact(&self, path: &[String], value: &str) -> Option<Cow<'_, str>> {
+        self.0.redact(path, value)
+    }
+    fn elide_empty(&self) -> bool {
+        self.0.elide_empty()
+    }
+    fn list_brackets(&self) -> (&str, &str) {
+        self.0.list_brackets()
+    }
+    fn map_brackets(&self) -> (&str, &str) {
+        self.0.map_brackets()
+    }
+    fn set_brackets(&self) -> (&str, &str) {
+        self.0.set_brackets()
+    }
+    fn trailing_commas(&self) -> bool {
+        self.0.trailing_commas()
+    }
+    fn inline_threshold(&self) -> Option<usize> {
+        self.0.inline_threshold()
+    }
+    fn inline_element_width(&self) -> usize {
+        self.0.inline_element_width()
+    }
+    fn slice_indices(&self) -> bool {
+        self.0.slice_indices()
+    }
+    fn elision_marker(&self) -> &str {
+        self.0.elision_marker()
+    }
+    fn elision_marker_unknown(&self) -> &str {
+        self.0.elision_marker_unknown()
+    }
+    fn float_precision(&self) -> Option<usize> {
+        self.0.float_precision()
+    }
+    fn scientific_thresholds(&self) -> Option<(f64, f64)> {
+        self.0.scientific_thresholds()
+    }
+    fn thousands_separator(&self) -> Option<&str> {
+        self.0.thousands_separator()
+    }
+    #[cfg(feature = "icu")]
+    fn decimal_formatter(&self) -> Option<&crate::icu::decimal::DecimalFormatter> {
+        self.0.decimal_formatter()
+    }
+    fn bytes_format(&self) -> BytesFormat {
+        self.0.bytes_format()
+    }
+}
 
 //tt IndentedDisplay
-pub trait IndentedDisplay<'a, Opt: IndentedOptions<'a>> {
+/// The trait implemented by values that may be displayed with an
+/// [Indenter]; the options default to [crate::NullOptions], so that a
+/// value that does not use options may implement
+/// `IndentedDisplay<'_>` and take a `&mut Indenter<'_>`
+pub trait IndentedDisplay<'a, Opt: IndentedOptions = crate::NullOptions> {
     //mp fmt
     /// Display for humans with indent
     fn indent(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result;
+
+    //mp try_indent
+    /// Display with indent as for [IndentedDisplay::indent], returning
+    /// the cause of any failure (such as an error from the underlying
+    /// writer) with the scope path at which it occurred, rather than
+    /// a bare [std::fmt::Error]
+    fn try_indent(&self, f: &mut Indenter<'a, Opt>) -> crate::Result<()> {
+        let result = self.indent(f);
+        match (result, f.last_error()) {
+            (_, Some(e)) => Err(e),
+            (Err(e), None) => Err(Error::new(f.current_path(), e)),
+            _ => Ok(()),
+        }
+    }
+
+    //mp is_empty_value
+    /// Return true if the value is empty (such as None, an empty
+    /// string or an empty collection), so that it may be elided if
+    /// [IndentedOptions::elide_empty] is set
+    fn is_empty_value(&self) -> bool {
+        false
+    }
+
+    //mp summarize
+    /// Display a one-line summary of the value (without a newline),
+    /// used in place of its full display when it is too deep or too
+    /// long; by default this is a bare "…"
+    fn summarize(&self, f: &mut Indenter<'a, Opt>) -> std::fmt::Result {
+        use std::fmt::Write;
+        f.write_str("…")
+    }
+
+    //mp estimated_size
+    /// Return an estimate of the size of the output of the value with
+    /// the given options, if one can be made cheaply
+    ///
+    /// This is consulted when deciding if a value is displayed inline
+    /// or summarized; if it is None the value is rendered (and the
+    /// output discarded) to determine its size
+    fn estimated_size(&self, options: &'a Opt) -> Option<SizeEstimate> {
+        let _ = options;
+        None
+    }
+
+    //mp indent_to
+    /// Display to an [std::io::Write] with a new [Indenter], using the
+    /// given base indentation string and options
+    ///
+    /// Any error from the writer is returned, with the same kind as
+    /// the underlying error; its message includes the scope path at
+    /// which it occurred
+    fn indent_to(
+        &self,
+        w: &'a mut (dyn std::io::Write + 'a),
+        indent: &'a str,
+        options: &'a Opt,
+    ) -> std::io::Result<()> {
+        let mut ind = Indenter::new(w, indent, options);
+        self.try_indent(&mut ind)
+            .map_err(|e| std::io::Error::new(e.io_kind(), e))
+    }
+
+    //mp rendered_lines
+    /// Render with a new [Indenter], using the given base indentation
+    /// string and options, and return an iterator over the lines of
+    /// the output
+    ///
+    /// This is not lazy: the value is rendered in full, and the
+    /// output held in memory, before this returns. The lines are then
+    /// produced one at a time, so callers may filter, prefix or
+    /// paginate them as they wish; to handle the output as it is
+    /// written, display the value with an [Indenter] on a writer (see
+    /// [crate::IndentSink]) instead
+    fn rendered_lines(&self, indent: &'a str, options: &'a Opt) -> IndentedLines {
+        let recorder = Recorder::default();
+        {
+            let sink = Box::new(IoSink::new(recorder.clone()));
+            let mut ind = Indenter::with_sink(sink, indent, options);
+            // The recorder cannot fail, so only a formatting error is
+            // possible; the output up to that point is still returned
+            let _ = self.indent(&mut ind);
+        }
+        IndentedLines::new(recorder.take())
+    }
+
+    //mp indented_structure
+    /// Render with a new [Indenter], using the given options, and
+    /// return the lines of the output paired with the depth at which
+    /// each was written
+    ///
+    /// The lines do not include any indentation (nor page breaks), so
+    /// that the caller may present the structure as it wishes (for
+    /// example in a tree view) without parsing the indentation
+    fn indented_structure(&self, options: &'a Opt) -> Vec<(usize, String)> {
+        let recorder = DepthRecorder::default();
+        {
+            let mut ind = Indenter::with_sink(Box::new(recorder.clone()), "", options);
+            // The recorder cannot fail, so only a formatting error is
+            // possible; the output up to that point is still returned
+            let _ = self.indent(&mut ind);
+        }
+        recorder
+            .take()
+            .into_iter()
+            .map(|(depth, _, text)| (depth, text))
+            .collect()
+    }
 }
 
 //tt DefaultIndentedDisplay
-pub trait DefaultIndentedDisplay: std::fmt::Display {}
+pub trait DefaultIndentedDisplay: std::fmt::Display {
+    //mp indented_text
+    /// The text to display for the value with the given options; by
+    /// default this is given by its [std::fmt::Display]
+    /// implementation, and the numeric types override it to honor
+    /// the numeric formatting options
+    fn indented_text<O: IndentedOptions>(&self, options: &O) -> String {
+        let _ = options;
+        self.to_string()
+    }
+}
@@ -17,5 +17,379 @@ limitations under the License.
  */
 
 //a Types
+//tp IndentedLines
+/// An iterator over the lines of output that has already been
+/// rendered in full, as returned by
+/// [crate::IndentedDisplay::rendered_lines]
+///
+/// Each line is returned without its newline; if the output does not
+/// end with a newline then the last line is returned as it is.
+#[derive(Debug, Clone)]
+pub struct IndentedLines {
+    /// The rendered output
+    text: String,
+    /// The position in the text of the next line
+    pos: usize,
+}
+
+//ip IndentedLines
+impl IndentedLines {
+    //fp new
+    /// Create a new iterator over the lines of some text
+    pub(crate) fn new(text: String) -> Self {
+        Self { text, pos: 0 }
+    }
+}
+
+//ip Iterator for IndentedLines
+impl Iterator for IndentedLines {
+    type Item = String;
+    fn next(&mut self) -> Option<String> {
+        if self.pos >= self.text.len() {
+            return None;
+        }
+        let rest = &self.text[self.pos..];
+        match rest.find('\n') {
+            Some(n) => {
+                self.pos += n + 1;
+                Some(rest[..n].to_string())
+            }
+            None => {
+                self.pos = self.text.len();
+                Some(rest.to_string())
+            }
+        }
+    }
+}
+
 //tp NullOptions
 pub struct NullOptions {}
+
+//tp BasicOptions
+/// Options covering the common settings of an indenter, so that a
+/// type of options need not be written for them
+///
+/// These are created with [BasicOptions::new] (giving the default
+/// options, as for [NullOptions]) and set with the `with_` methods:
+///
+/// ```
+/// # use indent_display::{BasicOptions, IndentedDisplay};
+/// let options = BasicOptions::new().with_indent_width(2).with_max_width(80);
+/// let lines: Vec<String> = vec![1, 2].rendered_lines("", &options).collect();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasicOptions {
+    /// The width in spaces of each level of indentation, if set
+    pub(crate) indent_width: Option<usize>,
+    /// The character with which each level of indentation is made
+    pub(crate) indent_style: IndentStyle,
+    /// The maximum depth of values displayed in full, if limited
+    pub(crate) max_depth: Option<usize>,
+    /// The maximum width of a line of output, if limited
+    pub(crate) max_width: Option<usize>,
+    /// The verbosity level of the output
+    pub(crate) verbosity: usize,
+    /// True if the output may include terminal color styling
+    pub(crate) color: bool,
+    /// The line ending of every line of output
+    pub(crate) line_ending: &'static str,
+    /// The maximum number of consecutive blank lines output
+    pub(crate) max_blank_lines: usize,
+}
+
+//ip Default for BasicOptions
+impl Default for BasicOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: None,
+            indent_style: IndentStyle::Spaces,
+            max_depth: None,
+            max_width: None,
+            verbosity: 0,
+            color: false,
+            line_ending: "\n",
+            max_blank_lines: 0,
+        }
+    }
+}
+
+//ip BasicOptions
+impl BasicOptions {
+    //fp new
+    /// Create a new [BasicOptions], with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //fp from_env
+    /// Create a new [BasicOptions] from the environment, so that the
+    /// user of a tool may configure its output
+    ///
+    /// The variables are:
+    ///
+    /// * `INDENT_WIDTH` - the width of each level of indentation
+    /// * `INDENT_STYLE` - `spaces` or `tabs`
+    /// * `INDENT_MAX_DEPTH` - the maximum depth of values displayed in
+    ///   full
+    /// * `INDENT_MAX_WIDTH` - the maximum width of a line of output
+    ///
+    /// Variables that are not set, or whose values are not valid, are
+    /// ignored, leaving the default options
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    //fi from_lookup
+    /// Create a new [BasicOptions] from variables as for
+    /// [BasicOptions::from_env], looking them up with a function
+    pub(crate) fn from_lookup<F: Fn(&str) -> Option<String>>(lookup: F) -> Self {
+        let number = |name| lookup(name).and_then(|v| v.trim().parse::<usize>().ok());
+        let indent_style = match lookup("INDENT_STYLE").as_deref().map(str::trim) {
+            Some("tabs") => IndentStyle::Tabs,
+            _ => IndentStyle::Spaces,
+        };
+        Self {
+            indent_width: number("INDENT_WIDTH"),
+            indent_style,
+            max_depth: number("INDENT_MAX_DEPTH"),
+            max_width: number("INDENT_MAX_WIDTH"),
+            ..Self::default()
+        }
+    }
+
+    //cp with_indent_width
+    /// Set the width in spaces of each level of indentation, replacing
+    /// the base indentation string given to the indenter
+    pub fn with_indent_width(mut self, width: usize) -> Self {
+        self.indent_width = Some(width);
+        self
+    }
+
+    //cp with_indent_style
+    /// Set the character with which each level of indentation is made
+    pub fn with_indent_style(mut self, style: IndentStyle) -> Self {
+        self.indent_style = style;
+        self
+    }
+
+    //cp with_max_depth
+    /// Set the maximum depth at which values are displayed in full
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    //cp with_max_width
+    /// Set the maximum width of a line of output, including its
+    /// indentation
+    pub fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    //cp with_verbosity
+    /// Set the verbosity level of the output, such as the number of
+    /// `-v` flags given to a command line tool
+    pub fn with_verbosity(mut self, verbosity: usize) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    //cp with_color
+    /// Set whether the output may include terminal color styling
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    //cp with_line_ending
+    /// Set the line ending of every line of output, such as "\r\n"
+    pub fn with_line_ending(mut self, line_ending: &'static str) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    //cp with_max_blank_lines
+    /// Set the maximum number of consecutive blank lines output from
+    /// the text written to an indenter
+    pub fn with_max_blank_lines(mut self, max: usize) -> Self {
+        self.max_blank_lines = max;
+        self
+    }
+}
+
+//tp DepthChange
+/// The kind of change of depth reported to an observer registered
+/// with [crate::Indenter::observe_depth]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthChange {
+    /// A frame has been pushed; the depth is that of the new frame
+    Push,
+    /// A frame has been popped; the depth is that of its parent
+    Pop,
+}
+
+//tp Overflow
+/// The policy for lines that exceed the maximum width given by
+/// [crate::IndentedOptions::max_width]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Continue the line on the next line, at the same indentation
+    #[default]
+    Wrap,
+    /// Truncate the line, ending it with the truncation marker
+    Truncate,
+    /// Output the line in full, but record an error that is reported
+    /// by [crate::Indenter::last_error]
+    Error,
+}
+
+//tp Severity
+/// The severity with which output may be tagged, with
+/// [crate::Indenter::tagged] or [crate::Indenter::sub_tagged]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Information
+    Info,
+    /// A warning
+    Warn,
+    /// An error
+    Error,
+}
+
+//ip Severity
+impl Severity {
+    //fi index
+    /// The index of the severity, for counting
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+//tp ScopeTiming
+/// The timing of named scopes, as given by
+/// [crate::IndentedOptions::scope_timing]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScopeTiming {
+    /// Named scopes are not timed
+    #[default]
+    Off,
+    /// The duration of each named scope is appended to its header
+    /// line - the line that was in progress when it was pushed - so
+    /// the output of the scope is buffered until it is popped
+    Headers,
+    /// The duration of each named scope is output in a summary at the
+    /// end of the output, one line per scope
+    Summary,
+}
+
+//tp DeepFrames
+/// The treatment of the output of frames deeper than the maximum
+/// depth, as given by [crate::IndentedOptions::deep_frames]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeepFrames {
+    /// The output is indented as normal
+    #[default]
+    Indent,
+    /// The output is indented as if it were at the maximum depth
+    Flatten,
+    /// The output is discarded, and replaced by the depth marker of
+    /// the options once for each frame at the maximum depth
+    Elide,
+}
+
+//tp FinalNewline
+/// Whether the last line of output is ended with a newline, as given
+/// by [crate::IndentedOptions::final_newline]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FinalNewline {
+    /// The last line is ended if it was ended as written, or if a
+    /// frame was popped after it was written
+    #[default]
+    AsWritten,
+    /// The last line is always ended, unless there is no output
+    Always,
+    /// The last line is never ended; its newline is removed
+    Never,
+}
+
+//tp IndentStyle
+/// The character with which each level of indentation is made, as
+/// given by [crate::IndentedOptions::indent_style]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndentStyle {
+    /// Spaces, as many as the indent width
+    #[default]
+    Spaces,
+    /// Tabs, as many as the indent width (or one, if it is not set)
+    Tabs,
+}
+
+//tp FlushPolicy
+/// When the writer of an indenter is flushed, as given by
+/// [crate::IndentedOptions::flush_policy]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlushPolicy {
+    /// The writer is flushed only by [crate::Indenter::flush]
+    #[default]
+    Manual,
+    /// The writer is flushed after every line - for (for example)
+    /// progress output to a terminal or a pipe
+    EachLine,
+    /// The writer is flushed at the end of each top-level block (when
+    /// the first line at the base depth after a top-level frame was
+    /// popped is completed), and when the output is complete
+    EachBlock,
+}
+
+//tp SizeEstimate
+/// An estimate of the size of the output of a value, as returned by
+/// [crate::IndentedDisplay::estimated_size]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SizeEstimate {
+    /// The number of lines of output
+    pub lines: usize,
+    /// The width in characters of the widest line, excluding any
+    /// indentation
+    pub width: usize,
+}
+
+//tp Metrics
+/// The measurements of the output of a value, as returned by
+/// [crate::Indenter::measure]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Metrics {
+    /// The number of lines of output
+    pub lines: usize,
+    /// The width in characters of the widest line, including its
+    /// indentation
+    pub width: usize,
+    /// The greatest depth of indentation of any line
+    pub depth: usize,
+}
+
+//tp RenderStats
+/// Statistics of the output written by an indenter, as returned by
+/// [crate::Indenter::stats]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    /// The number of bytes written to the output
+    pub bytes: usize,
+    /// The number of lines written to the output
+    pub lines: usize,
+    /// The greatest depth of indentation of any line written
+    pub depth: usize,
+}
+
+//tp BytesFormat
+/// The format used to display byte data, such as with [crate::Bytes]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytesFormat {
+    /// Hexadecimal bytes, 16 per line
+    Hex,
+    /// Offset, hexadecimal bytes and printable ASCII, 16 bytes per
+    /// line (in the style of `hexdump -C`)
+    HexAscii,
+    /// Standard base64 with padding, 76 characters per line
+    Base64,
+}